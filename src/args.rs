@@ -1,12 +1,28 @@
-use clap::{arg, Parser};
+use clap::{arg, Parser, Subcommand, ValueEnum};
 
 #[derive(Parser, Debug)]
 pub struct BalanceArgs {
     #[arg(
         value_name = "ADDRESS",
-        help = "The account address to fetch the balance of."
+        help = "The account addresses to fetch the balances of. Defaults to the signer."
     )]
-    pub address: Option<String>,
+    pub addresses: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "FILEPATH",
+        help = "File with one address per line to fetch the balances of."
+    )]
+    pub file: Option<String>,
+
+    #[arg(
+        long,
+        help = "Fetch the balances of every wallet in the encrypted keystore."
+    )]
+    pub all_wallets: bool,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
 }
 
 #[derive(Parser, Debug)]
@@ -19,10 +35,16 @@ pub struct BenchmarkArgs {
         default_value = "1"
     )]
     pub cores: u64,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
 }
 
 #[derive(Parser, Debug)]
-pub struct BussesArgs {}
+pub struct BussesArgs {
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+}
 
 #[derive(Parser, Debug)]
 pub struct ClaimArgs {
@@ -43,16 +65,43 @@ pub struct ClaimArgs {
         long,
         short,
         value_name = "POOL_URL",
-        help = "The optional pool url to claim rewards from."
+        value_delimiter = ',',
+        help = "The optional pool urls to claim rewards from. The first reachable pool is used."
     )]
-    pub pool_url: Option<String>,
+    pub pool_url: Vec<String>,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+
+    #[command(flatten)]
+    pub remote: RemoteSignerArgs,
+}
+
+#[derive(Parser, Debug)]
+pub struct CloseArgs {
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
 }
 
 #[derive(Parser, Debug)]
-pub struct CloseArgs {}
+pub struct ConfigArgs {
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+}
 
 #[derive(Parser, Debug)]
-pub struct ConfigArgs {}
+pub struct FeeArgs {
+    #[arg(
+        long,
+        value_name = "SLOTS",
+        help = "The number of recent slots to display.",
+        default_value = "20"
+    )]
+    pub slots: usize,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+}
 
 #[cfg(feature = "admin")]
 #[derive(Parser, Debug)]
@@ -78,23 +127,370 @@ pub struct MineArgs {
     )]
     pub buffer_time: u64,
 
+    #[arg(
+        long,
+        value_name = "AUTHORITY_ADDRESS",
+        help = "Mine for the proof of this authority with a delegated miner key."
+    )]
+    pub authority: Option<String>,
+
     #[arg(
         long,
         short,
         value_name = "POOL_URL",
-        help = "The optional pool url to join and forward solutions to."
+        value_delimiter = ',',
+        help = "The optional pool urls to join and forward solutions to, in order of preference."
     )]
-    pub pool_url: Option<String>,
+    pub pool_url: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Fall back to solo mining when none of the pools are reachable."
+    )]
+    pub solo_fallback: bool,
+
+    #[arg(
+        long,
+        value_name = "OPERATOR_ADDRESS",
+        value_delimiter = ',',
+        help = "Pin the operators of off-chain pools, such as pool-server, that have no on-chain pool account."
+    )]
+    pub pool_operator: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Accept unsigned pool responses when the pool operator cannot be determined. Unsafe."
+    )]
+    pub insecure_pool: bool,
+
+    #[arg(
+        long,
+        value_name = "RATIO",
+        help = "Skip submissions whose expected reward is worth less than this multiple of the transaction cost.",
+        requires = "ore_price"
+    )]
+    pub min_profit_ratio: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "SOL",
+        help = "The price of 1 ORE in SOL, used to value rewards against transaction fees."
+    )]
+    pub ore_price: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "SOL",
+        help = "The maximum SOL to spend on a single mining transaction."
+    )]
+    pub max_sol_per_round: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "SOL",
+        help = "The maximum SOL to spend on mining transactions in any one hour."
+    )]
+    pub max_sol_per_hour: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "SOL",
+        help = "The maximum SOL to spend on mining transactions in any one day."
+    )]
+    pub max_sol_per_day: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "ACTION",
+        help = "What to do once the hourly or daily SOL budget is exhausted.",
+        default_value = "pause"
+    )]
+    pub budget_exhausted: BudgetAction,
+
+    #[arg(
+        long,
+        value_name = "FILEPATH",
+        help = "Filepath to persist SOL spending to. Defaults to ~/.config/luckycoin/spend.json."
+    )]
+    pub budget_file: Option<String>,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+
+    #[command(flatten)]
+    pub remote: RemoteSignerArgs,
+
+    #[command(flatten)]
+    pub fees: FeePayerArgs,
+
+    #[command(flatten)]
+    pub top_up: TopUpArgs,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BudgetAction {
+    /// Stop submitting until spending falls back under budget.
+    Pause,
+    /// Keep submitting with a zero priority fee.
+    ZeroFee,
+}
+
+#[derive(Parser, Debug)]
+pub struct MockPoolArgs {
+    #[arg(
+        long,
+        value_name = "PORT",
+        help = "The port to serve the mock pool on.",
+        default_value = "3000"
+    )]
+    pub port: u16,
+
+    #[arg(
+        long,
+        value_name = "FILEPATH",
+        help = "JSON file with the scripted challenge sequence. Defaults to random challenges."
+    )]
+    pub script: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "How long each random challenge is served for.",
+        default_value = "60"
+    )]
+    pub round_time: u64,
+
+    #[arg(
+        long,
+        value_name = "DIFFICULTY",
+        help = "The min difficulty of random challenges.",
+        default_value = "1"
+    )]
+    pub min_difficulty: u64,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+}
+
+#[derive(Parser, Debug)]
+pub struct PoolServerArgs {
+    #[arg(
+        long,
+        value_name = "PORT",
+        help = "The port to serve the pool on.",
+        default_value = "3000"
+    )]
+    pub port: u16,
+
+    #[arg(
+        long,
+        value_name = "HOST",
+        help = "The address to bind the pool server to.",
+        default_value = "0.0.0.0"
+    )]
+    pub host: String,
+
+    #[arg(
+        long,
+        short,
+        value_name = "SECONDS",
+        help = "The number seconds before the deadline to stop collecting solutions and submit the best one.",
+        default_value = "5"
+    )]
+    pub buffer_time: u64,
+
+    #[arg(
+        long,
+        value_name = "FILEPATH",
+        help = "File to store pool members and balances in. Defaults to ~/.config/luckycoin/pool-server.json."
+    )]
+    pub state_file: Option<String>,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+}
+
+#[derive(Parser, Debug)]
+pub struct OpenArgs {
+    #[arg(
+        long,
+        value_name = "MINER_ADDRESS",
+        help = "The key allowed to mine with the proof. Defaults to the authority."
+    )]
+    pub miner: Option<String>,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+
+    #[command(flatten)]
+    pub remote: RemoteSignerArgs,
+}
+
+#[derive(Parser, Debug)]
+pub struct PoolArgs {
+    #[command(subcommand)]
+    pub command: PoolCommand,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PoolCommand {
+    #[command(about = "Show your pool membership, balances and recent round credits")]
+    Status(PoolStatusArgs),
+
+    #[command(about = "Stake ORE into a pool share account")]
+    Stake(PoolStakeArgs),
+
+    #[command(about = "Withdraw staked ORE from a pool share account")]
+    Unstake(PoolUnstakeArgs),
+
+    #[command(about = "List every member's pool share and its part of the pool's stake multiplier")]
+    Shares(PoolSharesArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct PoolStatusArgs {
+    #[arg(
+        long,
+        short,
+        value_name = "POOL_URL",
+        help = "The pool url to show the member status of."
+    )]
+    pub pool_url: String,
+
+    #[arg(
+        long,
+        value_name = "OPERATOR_ADDRESS",
+        help = "Pin the operator of an off-chain pool that has no on-chain pool account."
+    )]
+    pub pool_operator: Option<String>,
+
+    #[arg(
+        long,
+        help = "Accept unsigned pool responses when the pool operator cannot be determined. Unsafe."
+    )]
+    pub insecure_pool: bool,
+
+    #[arg(
+        long,
+        value_name = "COUNT",
+        help = "The number of recent rounds to show.",
+        default_value = "10"
+    )]
+    pub rounds: usize,
+}
+
+#[derive(Parser, Debug)]
+pub struct PoolStakeArgs {
+    #[arg(
+        long,
+        short,
+        value_name = "POOL_URL",
+        help = "The pool url to stake into."
+    )]
+    pub pool_url: String,
+
+    #[arg(
+        value_name = "AMOUNT",
+        help = "The amount of ORE to stake. Defaults to max."
+    )]
+    pub amount: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "TOKEN_ACCOUNT_ADDRESS",
+        help = "Token account to send ORE from. Defaults to the associated token account."
+    )]
+    pub token_account: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+pub struct PoolUnstakeArgs {
+    #[arg(
+        long,
+        short,
+        value_name = "POOL_URL",
+        help = "The pool url to unstake from."
+    )]
+    pub pool_url: String,
+
+    #[arg(
+        value_name = "AMOUNT",
+        help = "The amount of ORE to unstake. Defaults to max."
+    )]
+    pub amount: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "WALLET_ADDRESS",
+        help = "Wallet to receive the unstaked ORE. Defaults to your own wallet."
+    )]
+    pub to: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+pub struct PoolSharesArgs {
+    #[arg(
+        long,
+        short,
+        value_name = "POOL_URL",
+        help = "The pool url to show your share of."
+    )]
+    pub pool_url: String,
 }
 
 #[derive(Parser, Debug)]
 pub struct ProofArgs {
     #[arg(value_name = "ADDRESS", help = "The address of the proof to fetch.")]
     pub address: Option<String>,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
 }
 
 #[derive(Parser, Debug)]
-pub struct RewardsArgs {}
+pub struct RewardsArgs {
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+}
+
+#[derive(Parser, Debug)]
+pub struct SignerDaemonArgs {
+    #[arg(
+        long,
+        value_name = "HOST",
+        help = "The address to bind the signer to.",
+        default_value = "127.0.0.1"
+    )]
+    pub host: String,
+
+    #[arg(
+        long,
+        value_name = "PORT",
+        help = "The port to serve the signer on.",
+        default_value = "7070"
+    )]
+    pub port: u16,
+
+    #[arg(
+        long,
+        value_name = "MICROLAMPORTS",
+        help = "Refuse to sign transactions with a higher compute unit price.",
+        default_value = "500000"
+    )]
+    pub max_cu_price: u64,
+
+    #[arg(
+        long,
+        help = "Start without LUCKYCOIN_SIGNER_TOKEN set, letting any local process request signatures."
+    )]
+    pub allow_no_token: bool,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+}
 
 #[derive(Parser, Debug)]
 pub struct StakeArgs {
@@ -110,18 +506,108 @@ pub struct StakeArgs {
         help = "Token account to send ORE from. Defaults to the associated token account."
     )]
     pub token_account: Option<String>,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+}
+
+#[derive(Parser, Debug)]
+pub struct SweepArgs {
+    #[arg(
+        value_name = "KEYPAIR_FILEPATH",
+        help = "Keypair files of the wallets to sweep.",
+        required_unless_present = "all_wallets"
+    )]
+    pub keypairs: Vec<String>,
+
+    #[arg(long, help = "Sweep every wallet in the encrypted keystore.")]
+    pub all_wallets: bool,
+
+    #[arg(
+        long,
+        value_name = "WALLET_ADDRESS",
+        help = "Wallet to consolidate ORE into. Defaults to the signer."
+    )]
+    pub to: Option<String>,
+
+    #[arg(long, help = "Also transfer surplus SOL to the destination wallet.")]
+    pub sol: bool,
+
+    #[arg(
+        long,
+        value_name = "SOL",
+        default_value = "0.01",
+        help = "SOL to leave in each wallet when sweeping SOL. Use 0 to sweep everything, otherwise at least 0.00089088 SOL (rent-exempt minimum)."
+    )]
+    pub keep_sol: f64,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+
+    #[command(flatten)]
+    pub fees: FeePayerArgs,
+
+    #[command(flatten)]
+    pub top_up: TopUpArgs,
 }
 
 #[derive(Parser, Debug)]
 pub struct TransferArgs {
-    #[arg(value_name = "AMOUNT", help = "The amount of ORE to transfer.")]
-    pub amount: f64,
+    #[arg(
+        value_name = "AMOUNT",
+        help = "The amount of ORE to transfer.",
+        required_unless_present = "batch"
+    )]
+    pub amount: Option<f64>,
 
     #[arg(
         value_name = "RECIPIENT_ADDRESS",
-        help = "The account address of the receipient."
+        help = "The account address of the receipient.",
+        required_unless_present = "batch"
     )]
-    pub to: String,
+    pub to: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FILEPATH",
+        conflicts_with_all = ["amount", "to"],
+        help = "CSV file of recipient addresses and ORE amounts to pay in bulk."
+    )]
+    pub batch: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FILEPATH",
+        requires = "batch",
+        help = "Filepath to track batch progress in. Defaults to the batch file with a .progress.jsonl extension."
+    )]
+    pub progress_file: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FILEPATH",
+        requires = "batch",
+        help = "Filepath to write transfer signatures to. Defaults to the batch file with a .receipt.csv extension."
+    )]
+    pub receipt: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "COUNT",
+        requires = "batch",
+        default_value = "20",
+        help = "Maximum number of transfers to pack into one transaction."
+    )]
+    pub max_per_tx: usize,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+
+    #[command(flatten)]
+    pub fees: FeePayerArgs,
+
+    #[command(flatten)]
+    pub top_up: TopUpArgs,
 }
 
 #[derive(Parser, Debug)]
@@ -131,6 +617,24 @@ pub struct UpgradeArgs {
         help = "The amount of ORE to upgrade from v1 to v2. Defaults to max."
     )]
     pub amount: Option<f64>,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+}
+
+#[derive(Parser, Debug)]
+pub struct UpdateMinerArgs {
+    #[arg(
+        value_name = "MINER_ADDRESS",
+        help = "The new key allowed to mine with your proof."
+    )]
+    pub miner: String,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+
+    #[command(flatten)]
+    pub remote: RemoteSignerArgs,
 }
 
 #[derive(Parser, Debug)]
@@ -140,4 +644,266 @@ pub struct UpdatePoolBalanceArgs {
         help = "The pool url from where to update on-chain balance."
     )]
     pub pool_url: String,
+
+    #[arg(
+        long,
+        value_name = "OPERATOR_ADDRESS",
+        help = "Pin the operator of an off-chain pool that has no on-chain pool account."
+    )]
+    pub pool_operator: Option<String>,
+
+    #[command(flatten)]
+    pub signer: WalletSelectorArgs,
+}
+
+#[derive(Parser, Debug)]
+pub struct WalletArgs {
+    #[command(subcommand)]
+    pub command: WalletCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WalletCommand {
+    #[command(about = "Generate a new keypair and store it in the encrypted keystore")]
+    New(WalletNewArgs),
+
+    #[command(about = "Encrypt an existing keypair file into the keystore")]
+    Import(WalletImportArgs),
+
+    #[command(about = "List the wallets in the keystore")]
+    List(WalletListArgs),
+
+    #[command(about = "Print the public key of a wallet")]
+    ExportPubkey(WalletNameArgs),
+
+    #[command(about = "Delete a wallet from the keystore")]
+    Remove(WalletNameArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct WalletNewArgs {
+    #[arg(value_name = "NAME", help = "The name of the new wallet.")]
+    pub name: String,
+
+    #[arg(
+        long,
+        help = "Derive the keypair from a newly generated seed phrase and print it for backup."
+    )]
+    pub mnemonic: bool,
+
+    #[arg(
+        long,
+        value_name = "COUNT",
+        help = "The number of words in the generated seed phrase.",
+        default_value = "12",
+        requires = "mnemonic"
+    )]
+    pub words: usize,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "The derivation path of the keypair. Defaults to m/44'/501'/0'/0'.",
+        requires = "mnemonic"
+    )]
+    pub derivation_path: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+pub struct WalletImportArgs {
+    #[arg(value_name = "NAME", help = "The name to store the wallet under.")]
+    pub name: String,
+
+    #[arg(
+        value_name = "KEYPAIR_FILEPATH",
+        help = "The plaintext keypair file to import.",
+        required_unless_present = "mnemonic",
+        conflicts_with = "mnemonic"
+    )]
+    pub keypair: Option<String>,
+
+    #[arg(
+        long,
+        help = "Recover the keypair from a seed phrase instead of a file."
+    )]
+    pub mnemonic: bool,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "The derivation path of the keypair. Defaults to m/44'/501'/0'/0'.",
+        requires = "mnemonic",
+        conflicts_with = "count"
+    )]
+    pub derivation_path: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "COUNT",
+        help = "Derive this many accounts (m/44'/501'/n'/0') and store them as NAME-n.",
+        requires = "mnemonic"
+    )]
+    pub count: Option<u32>,
+}
+
+#[derive(Parser, Debug)]
+pub struct WalletListArgs {}
+
+#[derive(Parser, Debug)]
+pub struct WalletNameArgs {
+    #[arg(value_name = "NAME", help = "The name of the wallet.")]
+    pub name: String,
+}
+
+/// 选择本地签名密钥：加密钱包库中的钱包，或从助记词按派生路径生成的密钥
+///
+/// 签名交易或矿池请求的命令都包含这组参数，未指定时使用 --keypair 指定的密钥文件。
+#[derive(Parser, Debug, Clone)]
+pub struct WalletSelectorArgs {
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        conflicts_with = "derivation_path",
+        help = "Sign with a wallet from the encrypted keystore instead of a keypair file."
+    )]
+    pub wallet: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        help = "Derivation path for keypairs entered as a seed phrase (prompt://). Defaults to m/44'/501'/0'/0'."
+    )]
+    pub derivation_path: Option<String>,
+}
+
+/// 通过签名服务（signer-daemon）以证明 authority 的身份签名，本机不保存 authority 私钥
+///
+/// 挖矿、领取、开启证明和更新矿工的命令包含这组参数。
+#[derive(Parser, Debug)]
+pub struct RemoteSignerArgs {
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        help = "Sign as the proof authority through a signer daemon instead of a local keypair."
+    )]
+    pub remote_signer: Option<String>,
+}
+
+/// 在多个费用支付者之间轮换交易费用，余额耗尽的账户会被跳过
+///
+/// 挖矿、转账和归集命令包含这组参数，未指定时由默认的 fee payer 支付。
+#[derive(Parser, Debug)]
+pub struct FeePayerArgs {
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILEPATHS",
+        value_delimiter = ',',
+        help = "Comma-separated keypair files to rotate transaction fees across. Depleted fee payers are skipped. Use prompt://?key=N/0 to derive several fee payers from one seed phrase."
+    )]
+    pub fee_payers: Vec<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "STRATEGY",
+        default_value = "round-robin",
+        help = "How to pick the next fee payer from --fee-payers."
+    )]
+    pub fee_payer_strategy: FeePayerStrategy,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeePayerStrategy {
+    /// Take turns between funded fee payers.
+    RoundRobin,
+    /// Always use the fee payer with the highest balance.
+    MostFunded,
+}
+
+/// 费用支付者余额不足时从资金钱包自动补充 SOL，每天的补充总额受上限约束
+///
+/// 与 --fee-payers 一起用于挖矿、转账和归集命令。
+#[derive(Parser, Debug)]
+pub struct TopUpArgs {
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILEPATH",
+        help = "Keypair to automatically top up fee payers from when their SOL balance runs low."
+    )]
+    pub funding_wallet: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "SOL",
+        default_value = "0.01",
+        help = "Top up a fee payer when its balance falls below this amount."
+    )]
+    pub top_up_threshold: f64,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "SOL",
+        default_value = "0.05",
+        help = "Amount of SOL to send per top-up."
+    )]
+    pub top_up_amount: f64,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "SOL",
+        default_value = "0.5",
+        help = "Maximum SOL the funding wallet sends per UTC day."
+    )]
+    pub top_up_daily_cap: f64,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Log the top-ups that would be sent without sending them."
+    )]
+    pub top_up_dry_run: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILEPATH",
+        help = "Filepath to log top-ups to. Defaults to ~/.config/luckycoin/top-ups.jsonl."
+    )]
+    pub top_up_log: Option<String>,
+}
+
+/// 查询类命令的输出格式，默认为文本，也可以输出 JSON、YAML 或 CSV 供脚本处理。
+/// 展开在顶层参数中，luckycoin --output json balance 和 luckycoin balance --output json 等价
+#[derive(Parser, Debug)]
+pub struct OutputArgs {
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "FORMAT",
+        default_value = "text",
+        help = "Output format for balance, proof, config, rewards, busses, benchmark and fee. Diagnostics go to stderr when not text."
+    )]
+    pub output: OutputFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text.
+    Text,
+    /// A JSON object, or an array for commands that list several records.
+    Json,
+    /// The same schema as JSON, encoded as YAML.
+    Yaml,
+    /// A header row followed by one row per record.
+    Csv,
 }
@@ -1,58 +1,191 @@
-use std::str::FromStr;
+use std::{fs, str::FromStr};
 
-use solana_program::pubkey::Pubkey;
-use solana_sdk::signature::Signer;
+use chrono::{Local, TimeZone};
+use colored::*;
+use luckycoin_api::state::Proof;
+use ore_api::consts::TOKEN_DECIMALS;
+use serde::Serialize;
+use solana_program::{native_token::lamports_to_sol, program_pack::Pack, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::amount_to_ui_amount;
 
 use crate::{
     args::BalanceArgs,
-    utils::{amount_u64_to_string, get_proof_with_authority},
+    error::Error,
+    keystore::list_wallets,
+    mine::calculate_multiplier,
+    output::{machine_output, print_list},
+    utils::{amount_u64_to_string, get_config, proof_pubkey, MAX_ACCOUNTS_PER_REQUEST},
     Miner,
 };
 
+/// 一个地址的 SOL 余额、ORE 钱包余额和证明中的质押
+pub struct BalanceRow {
+    pub address: Pubkey,
+    pub sol: u64,
+    pub ore: u64,
+    pub stake: u64,
+    pub multiplier: f64,
+    pub last_hash_at: Option<i64>,
+}
+
+/// balance 的机器可读输出，每个地址一条记录
+#[derive(Serialize)]
+struct BalanceRecord {
+    address: String,
+    sol: f64,
+    ore: f64,
+    staked: f64,
+    multiplier: f64,
+    last_hash_at: Option<i64>,
+}
+
 impl Miner {
-    // Query miner token balance
+    // 查询一个或多个地址的余额，以表格显示并汇总
     pub async fn balance(&self, args: BalanceArgs) {
-        // 获取当前签名者信息
-        let signer = self.signer();
-        // 根据提供的地址或签名者的公钥确定查询的地址
-        let address = if let Some(address) = args.address {
-            if let Ok(address) = Pubkey::from_str(&address) {
-                address
-            } else {
-                // 如果地址无效，则打印错误信息并退出函数
-                println!("Invalid address: {:?}", address);
+        if let Err(err) = self.select_signer(&args.signer) {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
+        let addresses = match self.balance_addresses(&args) {
+            Ok(addresses) => addresses,
+            Err(err) => {
+                eprintln!("{} {}", "ERROR".bold().red(), err);
                 return;
             }
-        } else {
-            // 如果没有提供地址，则使用签名者公钥
-            signer.pubkey()
         };
-        println!("打印钱包地址:{}", address);
-        // 通过RPC客户端获取指定地址的证明信息
-        let proof = get_proof_with_authority(&self.rpc_client, address).await;
-
-        // 获取与指定地址关联的代币账户地址
-        let token_account_address = spl_associated_token_account::get_associated_token_address(
-            &address,
-            &ore_api::consts::MINT_ADDRESS,
-        );
-        // 通过RPC客户端获取代币账户的余额
-        let token_balance = if let Ok(Some(token_account)) = self
-            .rpc_client
-            .get_token_account(&token_account_address)
-            .await
-        {
-            // 如果获取成功，返回账户的UI显示金额
-            token_account.token_amount.ui_amount_string
-        } else {
-            // 如果获取失败，默认余额为0
-            "0".to_string()
+        let rows = match self.get_balances(&addresses).await {
+            Ok(rows) => rows,
+            Err(err) => {
+                eprintln!("{} {}", "ERROR".bold().red(), err);
+                return;
+            }
         };
-        // 打印账户余额和质押金额
+
+        if let Some(format) = machine_output() {
+            let records: Vec<BalanceRecord> = rows
+                .iter()
+                .map(|row| BalanceRecord {
+                    address: row.address.to_string(),
+                    sol: lamports_to_sol(row.sol),
+                    ore: amount_to_ui_amount(row.ore, TOKEN_DECIMALS),
+                    staked: amount_to_ui_amount(row.stake, TOKEN_DECIMALS),
+                    multiplier: row.multiplier,
+                    last_hash_at: row.last_hash_at,
+                })
+                .collect();
+            print_list(format, &records);
+            return;
+        }
+
         println!(
-            "Balance: {} ORE\nStake: {} ORE",
-            token_balance,
-            amount_u64_to_string(proof.balance)
-        )
+            "{:<44} {:>14} {:>16} {:>16} {:>10} {:>20}",
+            "Address", "SOL", "ORE", "Staked", "Multiplier", "Last mined"
+        );
+        for row in rows.iter() {
+            println!(
+                "{:<44} {:>14} {:>16} {:>16} {:>10} {:>20}",
+                row.address.to_string(),
+                lamports_to_sol(row.sol),
+                amount_u64_to_string(row.ore),
+                amount_u64_to_string(row.stake),
+                format!("{:.2}x", row.multiplier),
+                row.last_hash_at
+                    .and_then(|time| Local.timestamp_opt(time, 0).single())
+                    .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or("-".to_string())
+            );
+        }
+        if rows.len().gt(&1) {
+            println!(
+                "{:<44} {:>14} {:>16} {:>16}",
+                "Total",
+                lamports_to_sol(rows.iter().map(|row| row.sol).sum()),
+                amount_u64_to_string(rows.iter().map(|row| row.ore).sum()),
+                amount_u64_to_string(rows.iter().map(|row| row.stake).sum()),
+            );
+        }
+    }
+
+    // 要查询的地址：命令行参数、地址文件和密钥库中的钱包，都未提供时为当前 authority
+    fn balance_addresses(&self, args: &BalanceArgs) -> Result<Vec<Pubkey>, Error> {
+        let mut sources = args.addresses.clone();
+        if let Some(ref file) = args.file {
+            let data = fs::read_to_string(file)
+                .map_err(|err| Error::Internal(format!("failed to read {}: {}", file, err)))?;
+            sources.extend(
+                data.lines()
+                    .map(|line| line.trim())
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(|line| line.to_string()),
+            );
+        }
+        let mut addresses = vec![];
+        for source in sources.iter() {
+            let address = Pubkey::from_str(source)
+                .map_err(|_| Error::Internal(format!("Invalid address: {}", source)))?;
+            addresses.push(address);
+        }
+        if args.all_wallets {
+            addresses.extend(list_wallets().iter().map(|wallet| wallet.pubkey));
+        }
+        if addresses.is_empty() {
+            addresses.push(self.authority());
+        }
+
+        // 去掉重复的地址，保持原有顺序
+        let mut unique = vec![];
+        for address in addresses {
+            if !unique.contains(&address) {
+                unique.push(address);
+            }
+        }
+        Ok(unique)
+    }
+
+    /// 批量查询每个地址的账户、ORE 代币账户和证明账户
+    pub async fn get_balances(&self, addresses: &[Pubkey]) -> Result<Vec<BalanceRow>, Error> {
+        let config = get_config(&self.rpc_client).await;
+        let keys: Vec<Pubkey> = addresses
+            .iter()
+            .flat_map(|address| {
+                [
+                    *address,
+                    get_associated_token_address(address, &ore_api::consts::MINT_ADDRESS),
+                    proof_pubkey(*address),
+                ]
+            })
+            .collect();
+        let mut accounts = Vec::with_capacity(keys.len());
+        for chunk in keys.chunks(MAX_ACCOUNTS_PER_REQUEST) {
+            accounts.extend(self.rpc_client.get_multiple_accounts(chunk).await?);
+        }
+
+        let rows = addresses
+            .iter()
+            .zip(accounts.chunks(3))
+            .map(|(address, accounts)| {
+                let sol = accounts[0].as_ref().map_or(0, |account| account.lamports);
+                let ore = accounts[1]
+                    .as_ref()
+                    .and_then(|account| spl_token::state::Account::unpack(&account.data).ok())
+                    .map_or(0, |token_account| token_account.amount);
+                let proof = accounts[2]
+                    .as_ref()
+                    .and_then(|account| Proof::try_from_bytes(&account.data).ok());
+                let stake = proof.map_or(0, |proof| proof.balance);
+                BalanceRow {
+                    address: *address,
+                    sol,
+                    ore,
+                    stake,
+                    multiplier: calculate_multiplier(stake, config.top_balance),
+                    last_hash_at: proof
+                        .map(|proof| proof.last_hash_at)
+                        .filter(|time| time.gt(&0)),
+                }
+            })
+            .collect();
+        Ok(rows)
     }
 }
@@ -1,16 +1,35 @@
 use std::{sync::Arc, time::Instant};
 
+use colored::*;
 use drillx::equix;
+use serde::Serialize;
 use solana_rpc_client::spinner;
 
-use crate::{args::BenchmarkArgs, Miner};
+use crate::{
+    args::BenchmarkArgs,
+    output::{machine_output, print_record},
+    Miner,
+};
 
 const TEST_DURATION: i64 = 30; //基准测试持续时间(秒)
 
+/// benchmark 的机器可读输出
+#[derive(Serialize)]
+struct BenchmarkRecord {
+    cores: u64,
+    duration: i64,
+    hashes: u64,
+    hashpower: u64,
+}
+
 impl Miner {
     // 用于进行性能基准测试:这段代码主要用于多线程并行计算来评估矿工的哈希能力。在指定的时间内，它在多个CPU核心上运行哈希计算，
     // 并最终输出每秒的哈希数量。这种基准测试可以用于优化矿工的性能或不同配置的哈希。
     pub async fn benchmark(&self, args: BenchmarkArgs) {
+        if let Err(err) = self.select_signer(&args.signer) {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
         // 检查线程的核心数是否有效
         self.check_num_cores(args.cores);
         // 初始化基准测试
@@ -74,6 +93,17 @@ impl Miner {
         }
 
         // 更新进度条并输出哈希能力
+        if let Some(format) = machine_output() {
+            progress_bar.finish_and_clear();
+            let record = BenchmarkRecord {
+                cores: args.cores,
+                duration: TEST_DURATION,
+                hashes: total_nonces,
+                hashpower: total_nonces.saturating_div(TEST_DURATION as u64),
+            };
+            print_record(format, &record);
+            return;
+        }
         progress_bar.finish_with_message(format!(
             "Hashpower: {} H/sec", // 输出每秒哈希数量
             total_nonces.saturating_div(TEST_DURATION as u64),
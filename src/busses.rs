@@ -1,20 +1,50 @@
+use colored::*;
 use luckycoin_api::{
     consts::{BUS_ADDRESSES, TOKEN_DECIMALS},
     state::Bus,
 };
+use serde::Serialize;
 
-use crate::Miner;
+use crate::{
+    args::BussesArgs,
+    output::{machine_output, print_list},
+    Miner,
+};
+
+/// busses 的机器可读输出，每个 Bus 一条记录
+#[derive(Serialize)]
+struct BusRecord {
+    id: u64,
+    rewards: f64,
+}
 
 impl Miner {
     // 这段代码主要功能是从多个地址获取账户数据并尝试将其转换为Bus对象，然后打印出每个Bus的ID和对应的奖励值。
     // 通过使用异步编程，代码能够高校地处理多个请求
-    pub async fn busses(&self) {
+    pub async fn busses(&self, args: BussesArgs) {
+        if let Err(err) = self.select_signer(&args.signer) {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
+        let format = machine_output();
         // 克隆RPC客户端，以便在异步环境中使用
         let client = self.rpc_client.clone();
+        let mut records = vec![];
         // 遍历预定义的Bus地址列表
         for address in BUS_ADDRESSES.iter() {
-            // 异步获取指定地址的账户数据，使用 unwrap() 来处理可能的错误
-            let data = client.get_account_data(address).await.unwrap();
+            // 异步获取指定地址的账户数据，查询失败时报告错误并退出
+            let data = match client.get_account_data(address).await {
+                Ok(data) => data,
+                Err(err) => {
+                    eprintln!(
+                        "{} Failed to fetch bus {}: {}",
+                        "ERROR".bold().red(),
+                        address,
+                        err
+                    );
+                    return;
+                }
+            };
             // 尝试将获取的字节数据转化为Bus类型
             match Bus::try_from_bytes(&data) {
                 // 如果转换成功
@@ -22,11 +52,20 @@ impl Miner {
                     // 将奖励地址转化为浮点数，并根据TOKEN_DECIMALS 调整小数点位数
                     let rewards = (bus.rewards as f64) / 10f64.powf(TOKEN_DECIMALS as f64);
                     // 打印出公交ID和对应的奖励值
-                    println!("Bus {}: {:} ORE", bus.id, rewards);
+                    if format.is_none() {
+                        println!("Bus {}: {:} ORE", bus.id, rewards);
+                    }
+                    records.push(BusRecord {
+                        id: bus.id,
+                        rewards,
+                    });
                 }
                 // 如果转换失败，捕获错误但不做任何处理
                 Err(_) => {}
             }
         }
+        if let Some(format) = format {
+            print_list(format, &records);
+        }
     }
 }
\ No newline at end of file
@@ -9,7 +9,8 @@ use spl_token::amount_to_ui_amount;
 use crate::{
     args::ClaimArgs,
     cu_limits::CU_LIMIT_CLAIM,
-    pool::Pool,
+    pool::{first_healthy, Pool},
+    remote_signer::connect_remote_signer,
     send_and_confirm::ComputeBudget,
     utils::{amount_f64_to_u64, ask_confirm, get_proof_with_authority},
     Miner,
@@ -18,26 +19,33 @@ use crate::{
 impl Miner {
     /// 处理奖励的领取操作
     pub async fn claim(&self, args: ClaimArgs) -> Result<(), crate::error::Error> {
-        match args.pool_url {
-            Some(ref pool_url) => {// 如果pool_url存在
-                let pool = &Pool {
-                    http_client: reqwest::Client::new(),
-                    pool_url: pool_url.clone(),
-                };
-                let _ = self.claim_from_pool(args, pool).await?;
-                Ok(())
-            }
-            None => {// 如果pool_url不存在
-                self.claim_from_proof(args).await;// 异步调用claim_from_proof方法，用于从证明中领取奖励
-                Ok(())
-            }
+        self.select_signer(&args.signer)?;
+        if let Some(ref url) = args.remote.remote_signer {
+            connect_remote_signer(url).await?;
         }
+        if args.pool_url.is_empty() {
+            // 没有指定矿池时，从证明中领取奖励
+            self.claim_from_proof(args).await;
+            return Ok(());
+        }
+
+        // 与挖矿相同的故障转移顺序：使用第一个可用的矿池
+        let pools: Vec<Pool> = args
+            .pool_url
+            .iter()
+            .map(|pool_url| Pool::new(pool_url.clone()))
+            .collect();
+        let Some(i) = first_healthy(&pools, 0).await else {
+            return Err(crate::error::Error::Internal("no pool is reachable".to_string()));
+        };
+        println!("Claiming from pool {}", pools[i].pool_url);
+        self.claim_from_pool(args, &pools[i]).await?;
+        Ok(())
     }
 
     /// 从证明中领取奖励中领取奖励
     pub async fn claim_from_proof(&self, args: ClaimArgs) {
-        let signer = self.signer();
-        let pubkey = signer.pubkey();
+        let pubkey = self.authority();
         let proof = get_proof_with_authority(&self.rpc_client, pubkey).await;
         let mut ixs = vec![];
         let beneficiary = match args.to {
@@ -104,12 +112,16 @@ impl Miner {
         pool: &Pool,
     ) -> Result<Signature, crate::error::Error> {
         let pool_address = pool.get_pool_address().await?;
-        let member = pool
+        // 只在链下记账的矿池没有链上成员账户，由运营商直接支付
+        let Ok(member) = pool
             .get_pool_member_onchain(self, pool_address.address)
-            .await?;
+            .await
+        else {
+            return self.claim_from_offchain_pool(args, pool).await;
+        };
         let mut ixs = vec![];
         let beneficiary = match args.to {
-            None => self.initialize_ata(self.signer().pubkey()).await,
+            None => self.initialize_ata(self.local_signer().pubkey()).await,
             Some(to) => {
                 // Create beneficiary token account, if needed
                 let wallet = Pubkey::from_str(&to).expect("Failed to parse wallet address");
@@ -125,7 +137,7 @@ impl Miner {
                 {
                     ixs.push(
                         spl_associated_token_account::instruction::create_associated_token_account(
-                            &self.signer().pubkey(),
+                            &self.local_signer().pubkey(),
                             &wallet,
                             &ore_api::consts::MINT_ADDRESS,
                             &spl_token::id(),
@@ -160,7 +172,7 @@ impl Miner {
 
         // Send and confirm
         ixs.push(ore_pool_api::sdk::claim(
-            self.signer().pubkey(),
+            self.local_signer().pubkey(),
             beneficiary,
             pool_address.address,
             pool_address.bump,
@@ -171,10 +183,37 @@ impl Miner {
             .map_err(From::from)
     }
 
+    /// 请求只在链下记账的矿池运营商把尚未支付的余额转入成员的代币账户
+    async fn claim_from_offchain_pool(
+        &self,
+        args: ClaimArgs,
+        pool: &Pool,
+    ) -> Result<Signature, crate::error::Error> {
+        if args.to.is_some() || args.amount.is_some() {
+            return Err(crate::error::Error::Internal(
+                "off-chain pools pay out the full unpaid balance to your own wallet; omit AMOUNT and --to".to_string(),
+            ));
+        }
+        let commit = pool.post_update_pool_balance(&self.local_signer()).await?;
+        if commit.paid.eq(&0) {
+            return Err(crate::error::Error::Internal(
+                "nothing to claim from the pool".to_string(),
+            ));
+        }
+        println!(
+            "Pool paid out {} ORE: {}",
+            amount_to_ui_amount(commit.paid, ore_api::consts::TOKEN_DECIMALS),
+            commit.signature
+        );
+        Signature::from_str(&commit.signature).map_err(|_| {
+            crate::error::Error::Internal("pool returned an invalid signature".to_string())
+        })
+    }
+
     // 初始化关联代币账户（ATA）
     async fn initialize_ata(&self, wallet: Pubkey) -> Pubkey {
         // Initialize client.
-        let signer = self.signer();
+        let signer = self.local_signer();
         let client = self.rpc_client.clone();
 
         // Build instructions.
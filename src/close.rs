@@ -3,7 +3,7 @@ use solana_sdk::signature::Signer;
 use spl_token::amount_to_ui_amount;
 
 use crate::{
-    args::ClaimArgs,
+    args::{ClaimArgs, CloseArgs, RemoteSignerArgs},
     send_and_confirm::ComputeBudget,
     utils::{ask_confirm, get_proof_with_authority},
     Miner,
@@ -11,9 +11,13 @@ use crate::{
 
 impl Miner {
     // 异步方法，用于关闭矿工账户
-    pub async fn close(&self) {
+    pub async fn close(&self, args: CloseArgs) {
+        if let Err(err) = self.select_signer(&args.signer) {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
         // 确认证明存在
-        let signer = self.signer(); // 获取签名者
+        let signer = self.local_signer(); // 获取签名者
         let proof = get_proof_with_authority(&self.rpc_client, signer.pubkey()).await;
 
         // Confirm the user wants to close.
@@ -32,7 +36,11 @@ impl Miner {
             self.claim_from_proof(ClaimArgs {
                 amount: None,
                 to: None,
-                pool_url: None,
+                pool_url: vec![],
+                signer: args.signer.clone(),
+                remote: RemoteSignerArgs {
+                    remote_signer: None,
+                },
             })
                 .await;
         }
@@ -1,14 +1,43 @@
 use colored::Colorize;
-use ore_api::consts::EPOCH_DURATION;
+use ore_api::consts::{EPOCH_DURATION, TOKEN_DECIMALS};
+use serde::Serialize;
+use spl_token::amount_to_ui_amount;
 
 use crate::{
+    args::ConfigArgs,
+    output::{machine_output, print_record},
     utils::{amount_u64_to_string, get_config},
     Miner,
 };
 
+/// config 的机器可读输出
+#[derive(Serialize)]
+struct ConfigRecord {
+    last_reset_at: i64,
+    min_difficulty: u64,
+    base_reward_rate: u64,
+    top_balance: f64,
+    epoch_duration: i64,
+}
+
 impl Miner {
-    pub async fn config(&self) {
+    pub async fn config(&self, args: ConfigArgs) {
+        if let Err(err) = self.select_signer(&args.signer) {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
         let config = get_config(&self.rpc_client).await;
+        if let Some(format) = machine_output() {
+            let record = ConfigRecord {
+                last_reset_at: config.last_reset_at,
+                min_difficulty: config.min_difficulty,
+                base_reward_rate: config.base_reward_rate,
+                top_balance: amount_to_ui_amount(config.top_balance, TOKEN_DECIMALS),
+                epoch_duration: EPOCH_DURATION,
+            };
+            print_record(format, &record);
+            return;
+        }
         println!("{}: {}", "Last reset at".bold(), config.last_reset_at);
         println!("{}: {}", "Min difficulty".bold(), config.min_difficulty);
         println!("{}: {}", "Base reward rate".bold(), config.base_reward_rate);
@@ -1,4 +1,4 @@
-use crate::Miner;
+use crate::{fee_estimator::FeeEstimator, Miner};
 
 use luckycoin_api::consts::BUS_ADDRESSES;
 use reqwest::Client;
@@ -7,10 +7,18 @@ use serde_json::{json, Value};
 use solana_sdk::pubkey::Pubkey;
 use std::{collections::HashMap, str::FromStr};
 
-use solana_client::rpc_response::RpcPrioritizationFee;
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_response::RpcPrioritizationFee};
+use tokio::sync::OnceCell;
 
 use url::Url;
-enum FeeStrategy {
+
+type FeeError = Box<dyn std::error::Error + Send + Sync>;
+
+// 进程内共享的后台费用估算服务
+static FEE_ESTIMATOR: OnceCell<FeeEstimator> = OnceCell::const_new();
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeStrategy {
     Helius,
     Triton,
     LOCAL,
@@ -18,21 +26,14 @@ enum FeeStrategy {
     Quiknode,
 }
 
-impl Miner {
-    pub async fn dynamic_fee(&self) -> Result<u64, String> {
-        // Get url
-        let rpc_url = self
-            .dynamic_fee_url
-            .clone()
-            .unwrap_or(self.rpc_client.url());
-
-        // Select fee estiamte strategy
-        let host = Url::parse(&rpc_url)
-            .unwrap()
-            .host_str()
-            .unwrap()
-            .to_string();
-        let strategy = if host.contains("helius-rpc.com") {
+impl FeeStrategy {
+    /// 根据 RPC 地址的域名选择费用估算策略
+    pub fn from_url(rpc_url: &str) -> Self {
+        let host = Url::parse(rpc_url)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.to_string()))
+            .unwrap_or_default();
+        if host.contains("helius-rpc.com") {
             FeeStrategy::Helius
         } else if host.contains("alchemy.com") {
             FeeStrategy::Alchemy
@@ -42,164 +43,94 @@ impl Miner {
             FeeStrategy::Triton
         } else {
             FeeStrategy::LOCAL
-        };
-
-        // Build fee estimate request
-        let client = Client::new();
-        let ore_addresses: Vec<String> = std::iter::once(luckycoin_api::ID.to_string())
-            .chain(BUS_ADDRESSES.iter().map(|pubkey| pubkey.to_string()))
-            .collect();
-        let body = match strategy {
-            FeeStrategy::Helius => Some(json!({
-                "jsonrpc": "2.0",
-                "id": "priority-fee-estimate",
-                "method": "getPriorityFeeEstimate",
-                "params": [{
-                    "accountKeys": ore_addresses,
-                    "options": {
-                        "recommended": true
-                    }
-                }]
-            })),
-            FeeStrategy::Alchemy => Some(json!({
-                "jsonrpc": "2.0",
-                "id": "priority-fee-estimate",
-                "method": "getRecentPrioritizationFees",
-                "params": [
-                    ore_addresses
-                ]
-            })),
-            FeeStrategy::Quiknode => Some(json!({
-                "jsonrpc": "2.0",
-                "id": "1",
-                "method": "qn_estimatePriorityFees",
-                "params": {
-                    "account": "oreV2ZymfyeXgNgBdqMkumTqqAprVqgBWQfoYkrtKWQ",
-                    "last_n_blocks": 100
-                }
-            })),
-            FeeStrategy::Triton => Some(json!({
-                "jsonrpc": "2.0",
-                "id": "priority-fee-estimate",
-                "method": "getRecentPrioritizationFees",
-                "params": [
-                    ore_addresses,
-                    {
-                        "percentile": 5000,
-                    }
-                ]
-            })),
-            FeeStrategy::LOCAL => None,
-        };
-
-        // Send rpc request
-        let response = if let Some(body) = body {
-            let response: Value = client
-                .post(rpc_url)
-                .json(&body)
-                .send()
-                .await
-                .unwrap()
-                .json()
-                .await
-                .unwrap();
-            response
-        } else {
-            Value::Null
-        };
-
-        // Parse response
-        let calculated_fee = match strategy {
-            FeeStrategy::Helius => response["result"]["priorityFeeEstimate"]
-                .as_f64()
-                .map(|fee| fee as u64)
-                .ok_or_else(|| format!("Failed to parse priority fee response: {:?}", response)),
-            FeeStrategy::Quiknode => response["result"]["per_compute_unit"]["medium"]
-                .as_f64()
-                .map(|fee| fee as u64)
-                .ok_or_else(|| format!("Please enable the Solana Priority Fee API add-on in your QuickNode account.")),
-            FeeStrategy::Alchemy => response["result"]
-                .as_array()
-                .and_then(|arr| {
-                    Some(
-                        arr.into_iter()
-                            .map(|v| v["prioritizationFee"].as_u64().unwrap())
-                            .collect::<Vec<u64>>(),
-                    )
-                })
-                .and_then(|fees| {
-                    Some(
-                        ((fees.iter().sum::<u64>() as f32 / fees.len() as f32).ceil() * 1.2) as u64,
-                    )
-                })
-                .ok_or_else(|| format!("Failed to parse priority fee response: {:?}", response)),
-            FeeStrategy::Triton => {
-                serde_json::from_value::<Vec<RpcPrioritizationFee>>(response["result"].clone())
-                    .map(|prioritization_fees| {
-                        estimate_prioritization_fee_micro_lamports(prioritization_fees)
-                    })
-                    .or_else(|error: serde_json::Error| {
-                        Err(format!(
-                            "Failed to parse priority fee response: {response:?}, error: {error}"
-                        ))
-                    })
-            }
-            FeeStrategy::LOCAL => {
-                self.local_dynamic_fee().await.or_else(|err| {
-                    Err(format!("Failed to parse priority fee response: {err}"))
-                })
-            }
-        };
+        }
+    }
 
-        // Check if the calculated fee is higher than max
-        match calculated_fee {
-            Err(err) => Err(err),
-            Ok(fee) => {
-                if let Some(max_fee) = self.priority_fee {
-                    Ok(fee.min(max_fee))
-                } else {
-                    Ok(fee)
-                }
-            }
+    pub fn name(&self) -> &'static str {
+        match self {
+            FeeStrategy::Helius => "helius",
+            FeeStrategy::Triton => "triton",
+            FeeStrategy::LOCAL => "local",
+            FeeStrategy::Alchemy => "alchemy",
+            FeeStrategy::Quiknode => "quiknode",
         }
     }
+}
 
-    pub async fn local_dynamic_fee(&self) -> Result<u64, Box<dyn std::error::Error>> {
-        let client = self.rpc_client.clone();
-        let pubkey = [
-            "oreV2ZymfyeXgNgBdqMkumTqqAprVqgBWQfoYkrtKWQ",
-            "5HngGmYzvSuh3XyU11brHDpMTHXQQRQQT4udGFtQSjgR",
-            "2oLNTQKRb4a2117kFi6BYTUDu3RPrMVAHFhCfPKMosxX",
-        ];
-        let address_strings = pubkey;
+impl Miner {
+    pub async fn dynamic_fee(&self) -> Result<u64, String> {
+        // Get url
+        let rpc_url = self.dynamic_fee_rpc_url();
 
-        // Convert strings to Pubkey
-        let addresses: Vec<Pubkey> = address_strings
-            .into_iter()
-            .map(|addr_str| Pubkey::from_str(addr_str).expect("Invalid address"))
-            .collect();
+        // Select fee estiamte strategy
+        let strategy = FeeStrategy::from_url(&rpc_url);
+
+        // Check if the calculated fee is higher than max
+        self.estimate_fee(strategy, &rpc_url)
+            .await
+            .map(|fee| self.cap_fee(fee))
+    }
+
+    /// 从后台估算服务的缓存中读取动态费用，缓存过期时返回错误
+    pub async fn cached_dynamic_fee(&self) -> Result<u64, String> {
+        self.fee_estimator()
+            .await
+            .latest()
+            .map(|fee| self.cap_fee(fee))
+            .ok_or("Cached priority fee estimate is stale.".to_string())
+    }
+
+    /// 获取后台费用估算服务，首次调用时启动并等待第一次估算完成
+    pub async fn fee_estimator(&self) -> &'static FeeEstimator {
+        FEE_ESTIMATOR
+            .get_or_init(|| {
+                FeeEstimator::start(self.rpc_client.clone(), self.dynamic_fee_rpc_url())
+            })
+            .await
+    }
 
-        // Get recent prioritization fees
-        let recent_prioritization_fees = client.get_recent_prioritization_fees(&addresses).await?;
-        if recent_prioritization_fees.is_empty() {
-            return Err("No recent prioritization fees".into());
+    /// 当前交易会使用的优先费用：启用动态费用时读取缓存估算，过期则回退到静态值
+    pub async fn current_priority_fee(&self) -> u64 {
+        if self.dynamic_fee {
+            self.cached_dynamic_fee()
+                .await
+                .unwrap_or(self.priority_fee.unwrap_or(0))
+        } else {
+            self.priority_fee.unwrap_or(0)
         }
-        let mut sorted_fees: Vec<_> = recent_prioritization_fees.into_iter().collect();
-        sorted_fees.sort_by(|a, b| b.slot.cmp(&a.slot));
-        let chunk_size = 150;
-        let chunks: Vec<_> = sorted_fees.chunks(chunk_size).take(3).collect();
-        let mut percentiles: HashMap<u8, u64> = HashMap::new();
-        for (_, chunk) in chunks.iter().enumerate() {
-            let fees: Vec<u64> = chunk.iter().map(|fee| fee.prioritization_fee).collect();
-            percentiles = Self::calculate_percentiles(&fees);
+    }
+
+    /// 动态费用估算所使用的 RPC 地址，未配置时使用矿工的 RPC 地址
+    pub fn dynamic_fee_rpc_url(&self) -> String {
+        self.dynamic_fee_url
+            .clone()
+            .unwrap_or(self.rpc_client.url())
+    }
+
+    /// 使用静态优先费用作为上限
+    pub fn cap_fee(&self, fee: u64) -> u64 {
+        if let Some(max_fee) = self.priority_fee {
+            fee.min(max_fee)
+        } else {
+            fee
         }
+    }
 
-        // Default to 75 percentile
-        let fee = *percentiles.get(&75).unwrap_or(&0);
-        Ok(fee)
+    /// 使用指定策略估算优先费用（未应用上限）
+    pub async fn estimate_fee(&self, strategy: FeeStrategy, rpc_url: &str) -> Result<u64, String> {
+        estimate_fee(&Client::new(), &self.rpc_client, strategy, rpc_url).await
     }
 
-    fn calculate_percentiles(fees: &[u64]) -> HashMap<u8, u64> {
+    pub async fn local_dynamic_fee(&self) -> Result<u64, FeeError> {
+        local_dynamic_fee(&self.rpc_client).await
+    }
+
+    /// 获取本地 RPC 最近的分槽优先费用，按槽号从新到旧排序
+    pub async fn local_prioritization_fees(&self) -> Result<Vec<RpcPrioritizationFee>, FeeError> {
+        local_prioritization_fees(&self.rpc_client).await
+    }
+
+    pub(crate) fn calculate_percentiles(fees: &[u64]) -> HashMap<u8, u64> {
         let mut sorted_fees = fees.to_vec();
         sorted_fees.sort_unstable();
         let len = sorted_fees.len();
@@ -214,6 +145,163 @@ impl Miner {
     }
 }
 
+/// 使用指定策略估算优先费用（未应用上限），可在后台任务中独立于 Miner 调用
+pub async fn estimate_fee(
+    client: &Client,
+    rpc_client: &RpcClient,
+    strategy: FeeStrategy,
+    rpc_url: &str,
+) -> Result<u64, String> {
+    let ore_addresses: Vec<String> = std::iter::once(luckycoin_api::ID.to_string())
+        .chain(BUS_ADDRESSES.iter().map(|pubkey| pubkey.to_string()))
+        .collect();
+    let body = match strategy {
+        FeeStrategy::Helius => Some(json!({
+            "jsonrpc": "2.0",
+            "id": "priority-fee-estimate",
+            "method": "getPriorityFeeEstimate",
+            "params": [{
+                "accountKeys": ore_addresses,
+                "options": {
+                    "recommended": true
+                }
+            }]
+        })),
+        FeeStrategy::Alchemy => Some(json!({
+            "jsonrpc": "2.0",
+            "id": "priority-fee-estimate",
+            "method": "getRecentPrioritizationFees",
+            "params": [
+                ore_addresses
+            ]
+        })),
+        FeeStrategy::Quiknode => Some(json!({
+            "jsonrpc": "2.0",
+            "id": "1",
+            "method": "qn_estimatePriorityFees",
+            "params": {
+                "account": "oreV2ZymfyeXgNgBdqMkumTqqAprVqgBWQfoYkrtKWQ",
+                "last_n_blocks": 100
+            }
+        })),
+        FeeStrategy::Triton => Some(json!({
+            "jsonrpc": "2.0",
+            "id": "priority-fee-estimate",
+            "method": "getRecentPrioritizationFees",
+            "params": [
+                ore_addresses,
+                {
+                    "percentile": 5000,
+                }
+            ]
+        })),
+        FeeStrategy::LOCAL => None,
+    };
+
+    // Send rpc request
+    let response = if let Some(body) = body {
+        let response: Value = client
+            .post(rpc_url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|err| format!("Failed to send priority fee request: {err}"))?
+            .json()
+            .await
+            .map_err(|err| format!("Failed to decode priority fee response: {err}"))?;
+        response
+    } else {
+        Value::Null
+    };
+
+    // Parse response
+    match strategy {
+        FeeStrategy::Helius => response["result"]["priorityFeeEstimate"]
+            .as_f64()
+            .map(|fee| fee as u64)
+            .ok_or_else(|| format!("Failed to parse priority fee response: {:?}", response)),
+        FeeStrategy::Quiknode => response["result"]["per_compute_unit"]["medium"]
+            .as_f64()
+            .map(|fee| fee as u64)
+            .ok_or_else(|| {
+                format!(
+                    "Please enable the Solana Priority Fee API add-on in your QuickNode account."
+                )
+            }),
+        FeeStrategy::Alchemy => response["result"]
+            .as_array()
+            .and_then(|arr| {
+                Some(
+                    arr.into_iter()
+                        .map(|v| v["prioritizationFee"].as_u64().unwrap())
+                        .collect::<Vec<u64>>(),
+                )
+            })
+            .and_then(|fees| {
+                Some(((fees.iter().sum::<u64>() as f32 / fees.len() as f32).ceil() * 1.2) as u64)
+            })
+            .ok_or_else(|| format!("Failed to parse priority fee response: {:?}", response)),
+        FeeStrategy::Triton => {
+            serde_json::from_value::<Vec<RpcPrioritizationFee>>(response["result"].clone())
+                .map(|prioritization_fees| {
+                    estimate_prioritization_fee_micro_lamports(prioritization_fees)
+                })
+                .or_else(|error: serde_json::Error| {
+                    Err(format!(
+                        "Failed to parse priority fee response: {response:?}, error: {error}"
+                    ))
+                })
+        }
+        FeeStrategy::LOCAL => local_dynamic_fee(rpc_client)
+            .await
+            .or_else(|err| Err(format!("Failed to parse priority fee response: {err}"))),
+    }
+}
+
+pub async fn local_dynamic_fee(rpc_client: &RpcClient) -> Result<u64, FeeError> {
+    let sorted_fees = local_prioritization_fees(rpc_client).await?;
+    let chunk_size = 150;
+    let chunks: Vec<_> = sorted_fees.chunks(chunk_size).take(3).collect();
+    let mut percentiles: HashMap<u8, u64> = HashMap::new();
+    for (_, chunk) in chunks.iter().enumerate() {
+        let fees: Vec<u64> = chunk.iter().map(|fee| fee.prioritization_fee).collect();
+        percentiles = Miner::calculate_percentiles(&fees);
+    }
+
+    // Default to 75 percentile
+    let fee = *percentiles.get(&75).unwrap_or(&0);
+    Ok(fee)
+}
+
+/// 获取最近的分槽优先费用，按槽号从新到旧排序
+pub async fn local_prioritization_fees(
+    rpc_client: &RpcClient,
+) -> Result<Vec<RpcPrioritizationFee>, FeeError> {
+    let pubkey = [
+        "oreV2ZymfyeXgNgBdqMkumTqqAprVqgBWQfoYkrtKWQ",
+        "5HngGmYzvSuh3XyU11brHDpMTHXQQRQQT4udGFtQSjgR",
+        "2oLNTQKRb4a2117kFi6BYTUDu3RPrMVAHFhCfPKMosxX",
+    ];
+    let address_strings = pubkey;
+
+    // Convert strings to Pubkey
+    let addresses: Vec<Pubkey> = address_strings
+        .into_iter()
+        .map(|addr_str| Pubkey::from_str(addr_str).expect("Invalid address"))
+        .collect();
+
+    // Get recent prioritization fees
+    let recent_prioritization_fees = rpc_client
+        .get_recent_prioritization_fees(&addresses)
+        .await?;
+    if recent_prioritization_fees.is_empty() {
+        return Err("No recent prioritization fees".into());
+    }
+    let mut sorted_fees: Vec<_> = recent_prioritization_fees.into_iter().collect();
+    sorted_fees.sort_by(|a, b| b.slot.cmp(&a.slot));
+    Ok(sorted_fees)
+}
+
 /// Our estimate is the average over the last 20 slots
 pub fn estimate_prioritization_fee_micro_lamports(
     prioritization_fees: Vec<RpcPrioritizationFee>,
@@ -8,4 +8,14 @@ pub enum Error {
     SolanaRpcClient(#[from] solana_client::client_error::ClientError),
     #[error("solana program")]
     SolanaProgram(#[from] solana_program::program_error::ProgramError),
+    #[error("pool http status {0}: {1}")]
+    PoolStatus(u16, String),
+    #[error("pool response decode: {0}")]
+    PoolDecode(String),
+    #[error("pool signature: {0}")]
+    PoolSignature(String),
+    #[error("keystore: {0}")]
+    Keystore(String),
+    #[error("mnemonic: {0}")]
+    Mnemonic(String),
 }
\ No newline at end of file
@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+
+use colored::*;
+use serde::Serialize;
+
+use crate::{
+    args::{FeeArgs, OutputFormat},
+    dynamic_fee::FeeStrategy,
+    output::{machine_output, print_record},
+    Miner,
+};
+
+#[derive(Serialize)]
+struct FeeSource {
+    source: &'static str,
+    url: String,
+    fee: Option<u64>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SlotFee {
+    slot: u64,
+    fee: u64,
+}
+
+#[derive(Serialize)]
+struct FeeReport {
+    sources: Vec<FeeSource>,
+    percentiles: BTreeMap<u8, u64>,
+    recent_fees: Vec<SlotFee>,
+    dynamic_fee: bool,
+    priority_fee: Option<u64>,
+    fee: u64,
+}
+
+impl Miner {
+    // 并排运行所有费用来源，并显示矿工最终会使用的优先费用
+    pub async fn fee(&self, args: FeeArgs) {
+        if let Err(err) = self.select_signer(&args.signer) {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
+        // 费用报告包含嵌套的列表，无法展开为一行 CSV
+        let format = machine_output();
+        if format.eq(&Some(OutputFormat::Csv)) {
+            eprintln!(
+                "{} fee does not support --output csv. Use json or yaml.",
+                "ERROR".bold().red()
+            );
+            return;
+        }
+        let rpc_url = self.rpc_client.url();
+        let dynamic_fee_url = self.dynamic_fee_rpc_url();
+        let strategy = FeeStrategy::from_url(&dynamic_fee_url);
+
+        // 远程估算（如果配置的地址支持）和本地估算
+        let mut sources = vec![];
+        if strategy != FeeStrategy::LOCAL {
+            let estimate = self.estimate_fee(strategy, &dynamic_fee_url).await;
+            sources.push(fee_source(strategy, dynamic_fee_url.clone(), estimate));
+        }
+        let estimate = self.estimate_fee(FeeStrategy::LOCAL, &rpc_url).await;
+        sources.push(fee_source(FeeStrategy::LOCAL, rpc_url, estimate));
+
+        // 本地分槽费用和百分位表，与 local_dynamic_fee 使用相同的数据块
+        let (percentiles, recent_fees) = match self.local_prioritization_fees().await {
+            Ok(sorted_fees) => {
+                let percentiles = sorted_fees
+                    .chunks(150)
+                    .take(3)
+                    .last()
+                    .map(|chunk| {
+                        let fees: Vec<u64> =
+                            chunk.iter().map(|fee| fee.prioritization_fee).collect();
+                        Self::calculate_percentiles(&fees).into_iter().collect()
+                    })
+                    .unwrap_or_default();
+                let recent_fees = sorted_fees
+                    .iter()
+                    .take(args.slots)
+                    .map(|fee| SlotFee {
+                        slot: fee.slot,
+                        fee: fee.prioritization_fee,
+                    })
+                    .collect();
+                (percentiles, recent_fees)
+            }
+            Err(_) => (BTreeMap::new(), vec![]),
+        };
+
+        // 与 send_and_confirm 相同的选择逻辑
+        let fee = self.current_priority_fee().await;
+
+        let report = FeeReport {
+            sources,
+            percentiles,
+            recent_fees,
+            dynamic_fee: self.dynamic_fee,
+            priority_fee: self.priority_fee,
+            fee,
+        };
+        match format {
+            Some(format) => print_record(format, &report),
+            None => print_fee_report(&report),
+        }
+    }
+}
+
+fn fee_source(strategy: FeeStrategy, url: String, estimate: Result<u64, String>) -> FeeSource {
+    let (fee, error) = match estimate {
+        Ok(fee) => (Some(fee), None),
+        Err(err) => (None, Some(err)),
+    };
+    FeeSource {
+        source: strategy.name(),
+        url,
+        fee,
+        error,
+    }
+}
+
+fn print_fee_report(report: &FeeReport) {
+    println!("{}", "Sources".bold());
+    for source in report.sources.iter() {
+        match (source.fee, &source.error) {
+            (Some(fee), _) => println!("  {:<10} {:>12} microlamports", source.source, fee),
+            (None, Some(err)) => {
+                println!("  {:<10} {} {}", source.source, "ERROR".bold().red(), err)
+            }
+            (None, None) => {}
+        }
+    }
+
+    println!("\n{}", "Percentiles".bold());
+    for (percentile, fee) in report.percentiles.iter() {
+        println!("  p{:<9} {:>12} microlamports", percentile, fee);
+    }
+
+    println!("\n{}", "Recent slots".bold());
+    for slot_fee in report.recent_fees.iter() {
+        println!("  {:<10} {:>12} microlamports", slot_fee.slot, slot_fee.fee);
+    }
+
+    println!(
+        "\n{}: {}",
+        "Max priority fee".bold(),
+        report
+            .priority_fee
+            .map(|fee| format!("{} microlamports", fee))
+            .unwrap_or("none".to_string())
+    );
+    println!("{}: {}", "Dynamic fee".bold(), report.dynamic_fee);
+    println!("{}: {} microlamports", "Fee".bold(), report.fee);
+}
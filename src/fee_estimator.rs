@@ -0,0 +1,83 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use reqwest::Client;
+use solana_client::nonblocking::rpc_client::RpcClient;
+
+use crate::dynamic_fee::{estimate_fee, FeeStrategy};
+
+const FEE_REFRESH_INTERVAL: u64 = 5; // 后台刷新间隔（秒）
+const FEE_ESTIMATE_TTL: u64 = 30; // 估算值的有效期（秒）
+const FEE_REQUEST_TIMEOUT: u64 = 5; // 单次估算请求的超时（秒）
+
+#[derive(Clone, Copy)]
+struct FeeEstimate {
+    fee: u64,
+    updated_at: Instant,
+}
+
+/// 在后台定期刷新优先费用估算，提交交易时直接读取缓存值
+pub struct FeeEstimator {
+    latest: Arc<RwLock<Option<FeeEstimate>>>,
+}
+
+impl FeeEstimator {
+    /// 启动后台刷新任务，并等待第一次估算完成
+    pub async fn start(rpc_client: Arc<RpcClient>, rpc_url: String) -> Self {
+        let latest = Arc::new(RwLock::new(None));
+        let http_client = Client::builder()
+            .timeout(Duration::from_secs(FEE_REQUEST_TIMEOUT))
+            .build()
+            .unwrap_or_default();
+        let strategy = FeeStrategy::from_url(&rpc_url);
+
+        // 第一次估算
+        refresh(&latest, &http_client, &rpc_client, strategy, &rpc_url).await;
+
+        // 后台定期刷新
+        tokio::spawn({
+            let latest = latest.clone();
+            async move {
+                let mut interval = tokio::time::interval(Duration::from_secs(FEE_REFRESH_INTERVAL));
+                interval.tick().await;
+                loop {
+                    interval.tick().await;
+                    refresh(&latest, &http_client, &rpc_client, strategy, &rpc_url).await;
+                }
+            }
+        });
+
+        Self { latest }
+    }
+
+    /// 最新的估算值（未应用上限），超过有效期时返回 None
+    pub fn latest(&self) -> Option<u64> {
+        let estimate = (*self.latest.read().unwrap())?;
+        if estimate
+            .updated_at
+            .elapsed()
+            .ge(&Duration::from_secs(FEE_ESTIMATE_TTL))
+        {
+            return None;
+        }
+        Some(estimate.fee)
+    }
+}
+
+async fn refresh(
+    latest: &RwLock<Option<FeeEstimate>>,
+    http_client: &Client,
+    rpc_client: &RpcClient,
+    strategy: FeeStrategy,
+    rpc_url: &str,
+) {
+    // 估算失败时保留旧值，由有效期决定何时回退到静态费用
+    if let Ok(fee) = estimate_fee(http_client, rpc_client, strategy, rpc_url).await {
+        *latest.write().unwrap() = Some(FeeEstimate {
+            fee,
+            updated_at: Instant::now(),
+        });
+    }
+}
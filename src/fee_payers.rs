@@ -0,0 +1,144 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    OnceLock,
+};
+
+use colored::*;
+use solana_program::native_token::{lamports_to_sol, sol_to_lamports};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use crate::{
+    args::FeePayerStrategy,
+    error::Error,
+    mnemonic::{is_seed_phrase_prompt, load_keypair, load_keypairs},
+    Miner,
+};
+
+// 余额低于此值的费用支付者视为已耗尽（SOL）
+pub const MIN_SOL_BALANCE: f64 = 0.005;
+
+// 所有可用费用支付者的总余额低于此值时发出警告（SOL）
+const LOW_SOL_BALANCE: f64 = 0.05;
+
+// 配置后，交易在这些费用支付者之间轮换
+static FEE_PAYERS: OnceLock<FeePayerPool> = OnceLock::new();
+
+/// 多个费用支付者组成的资金池
+pub struct FeePayerPool {
+    keypairs: Vec<Keypair>,
+    strategy: FeePayerStrategy,
+    next: AtomicUsize,
+}
+
+// --fee-payer 为 prompt:// 时派生出的密钥，助记词只输入一次
+static PROMPTED_FEE_PAYER: OnceLock<[u8; 64]> = OnceLock::new();
+
+/// 加载 --fee-payers 指定的密钥，之后 send_and_confirm 在它们之间轮换
+///
+/// prompt:// 来源共用一次输入的助记词，例如 prompt://?key=0/0,prompt://?key=1/0。
+pub fn configure_fee_payers(sources: &[String], strategy: FeePayerStrategy) -> Result<(), Error> {
+    let keypairs = load_keypairs(sources)?;
+    if keypairs.is_empty() {
+        return Ok(());
+    }
+    FEE_PAYERS
+        .set(FeePayerPool {
+            keypairs,
+            strategy,
+            next: AtomicUsize::new(0),
+        })
+        .map_err(|_| Error::Internal("fee payers are already configured".to_string()))
+}
+
+impl Miner {
+    /// 单个费用支付者：--fee-payer 为 prompt:// 时按其中的派生路径从助记词生成，否则为 fee_payer()
+    pub fn local_fee_payer(&self) -> Keypair {
+        match self.fee_payer_filepath.as_deref() {
+            Some(source) if is_seed_phrase_prompt(source) => {
+                let bytes = PROMPTED_FEE_PAYER.get_or_init(|| {
+                    load_keypair(source, None)
+                        .expect("Failed to load fee payer")
+                        .to_bytes()
+                });
+                Keypair::from_bytes(bytes).expect("Failed to load fee payer")
+            }
+            _ => self.fee_payer(),
+        }
+    }
+
+    /// 所有候选费用支付者：配置了资金池时为池中的账户，否则为唯一的 fee payer
+    pub fn fee_payer_pubkeys(&self) -> Vec<Pubkey> {
+        match FEE_PAYERS.get() {
+            Some(pool) => pool
+                .keypairs
+                .iter()
+                .map(|keypair| keypair.pubkey())
+                .collect(),
+            None => vec![self.local_fee_payer().pubkey()],
+        }
+    }
+
+    /// 为下一笔交易选择余额充足的费用支付者，跳过已耗尽的账户，全部耗尽时返回错误
+    pub async fn select_fee_payer(&self) -> Result<Keypair, Error> {
+        // 查询所有费用支付者的余额
+        let pubkeys = self.fee_payer_pubkeys();
+        let accounts = self.rpc_client.get_multiple_accounts(&pubkeys).await?;
+        let mut balances: Vec<u64> = accounts
+            .iter()
+            .map(|account| account.as_ref().map_or(0, |account| account.lamports))
+            .collect();
+
+        // 配置了资金钱包时先补充余额不足的费用支付者
+        self.top_up_fee_payers(&pubkeys, &mut balances).await;
+
+        let funded: Vec<usize> = (0..balances.len())
+            .filter(|i| balances[*i].gt(&sol_to_lamports(MIN_SOL_BALANCE)))
+            .collect();
+        if funded.is_empty() {
+            return Err(insufficient_balance(balances.iter().sum()));
+        }
+        let Some(pool) = FEE_PAYERS.get() else {
+            return Ok(self.local_fee_payer());
+        };
+
+        // 可用的费用支付者即将耗尽时提前警告
+        let total: u64 = funded.iter().map(|i| balances[*i]).sum();
+        if funded.len().lt(&balances.len()) || total.lt(&sol_to_lamports(LOW_SOL_BALANCE)) {
+            println!(
+                "{} {} of {} fee payers funded ({} SOL left)",
+                "WARNING".bold().yellow(),
+                funded.len(),
+                balances.len(),
+                lamports_to_sol(total)
+            );
+        }
+
+        let index = match pool.strategy {
+            FeePayerStrategy::RoundRobin => {
+                let start = pool.next.fetch_add(1, Ordering::Relaxed) % balances.len();
+                (0..balances.len())
+                    .map(|offset| (start + offset) % balances.len())
+                    .find(|i| funded.contains(i))
+                    .unwrap_or(funded[0])
+            }
+            FeePayerStrategy::MostFunded => funded
+                .iter()
+                .copied()
+                .max_by_key(|i| balances[*i])
+                .unwrap_or(funded[0]),
+        };
+        Keypair::from_bytes(&pool.keypairs[index].to_bytes())
+            .map_err(|err| Error::Internal(err.to_string()))
+    }
+}
+
+fn insufficient_balance(balance: u64) -> Error {
+    Error::Internal(format!(
+        "Insufficient balance: {} SOL. Please top up with at least {} SOL",
+        lamports_to_sol(balance),
+        MIN_SOL_BALANCE
+    ))
+}
@@ -13,12 +13,12 @@ impl Miner {
 
         // 提交初始化交易
         let blockhash = self.rpc_client.get_latest_blockhash().await.unwrap();  // 获取最新的区块哈希
-        let ix = luckycoin_api::sdk::initialize(self.signer().pubkey()); // 创建初始化指令
+        let ix = luckycoin_api::sdk::initialize(self.local_signer().pubkey()); // 创建初始化指令
         // 创建一个新的交易，包含初始化指令，设置支付者为当前签名者
         let tx = Transaction::new_signed_with_payer(
             &[ix],  // 包含的指令
-            Some(&self.signer().pubkey()),  // 交易的支付者
-            &[&self.signer()],  // 签名者
+            Some(&self.local_signer().pubkey()),  // 交易的支付者
+            &[&self.local_signer()],  // 签名者
             blockhash,  // 最新的区块哈希
         );
         print!("发送交易并确认。。。。。。");
@@ -0,0 +1,323 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{Mutex, OnceLock},
+};
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use scrypt::{scrypt, Params};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use crate::{
+    args::WalletSelectorArgs,
+    error::Error,
+    mnemonic::{is_seed_phrase_prompt, load_keypair, SEED_PHRASE_PROMPT},
+    utils::config_path,
+    Miner,
+};
+
+const KEYSTORE_DIR: &str = "keystore";
+const KEYSTORE_VERSION: u8 = 1;
+
+// 无人值守的矿机可以通过环境变量提供口令，而不是交互输入
+pub const PASSPHRASE_ENV: &str = "LUCKYCOIN_WALLET_PASSPHRASE";
+
+// scrypt 参数：N = 2^15, r = 8, p = 1
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+// 已解锁的钱包，进程内只需输入一次口令
+static UNLOCKED: OnceLock<Mutex<HashMap<String, [u8; 64]>>> = OnceLock::new();
+
+// --wallet 或 --derivation-path 选择的签名密钥，设置后替代 --keypair 指定的密钥文件
+static SELECTED_SIGNER: OnceLock<[u8; 64]> = OnceLock::new();
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct KdfParams {
+    log_n: u8,
+    r: u32,
+    p: u32,
+}
+
+/// 磁盘上的加密钱包：公钥明文保存，私钥用口令派生的密钥以 AES-256-GCM 加密
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EncryptedWallet {
+    version: u8,
+    pub name: String,
+    pub pubkey: Pubkey,
+    kdf: KdfParams,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl EncryptedWallet {
+    /// 用口令加密密钥对
+    pub fn encrypt(name: &str, keypair: &Keypair, passphrase: &str) -> Result<Self, Error> {
+        let kdf = KdfParams {
+            log_n: SCRYPT_LOG_N,
+            r: SCRYPT_R,
+            p: SCRYPT_P,
+        };
+        let salt = rand::random::<[u8; 16]>();
+        let nonce = rand::random::<[u8; 12]>();
+        let cipher = cipher(passphrase, &salt, kdf)?;
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), keypair.to_bytes().as_ref())
+            .map_err(|_| Error::Keystore("failed to encrypt wallet".to_string()))?;
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            name: name.to_string(),
+            pubkey: keypair.pubkey(),
+            kdf,
+            salt: bs58::encode(salt).into_string(),
+            nonce: bs58::encode(nonce).into_string(),
+            ciphertext: bs58::encode(ciphertext).into_string(),
+        })
+    }
+
+    /// 用口令解密密钥对，口令错误时返回错误
+    pub fn decrypt(&self, passphrase: &str) -> Result<Keypair, Error> {
+        let salt = decode(&self.salt)?;
+        let nonce = decode(&self.nonce)?;
+        let ciphertext = decode(&self.ciphertext)?;
+        if nonce.len().ne(&12) {
+            return Err(Error::Keystore(format!(
+                "wallet {} is corrupted",
+                self.name
+            )));
+        }
+        let cipher = cipher(passphrase, &salt, self.kdf)?;
+        let secret = cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| Error::Keystore("incorrect passphrase".to_string()))?;
+        let keypair = Keypair::from_bytes(&secret)
+            .map_err(|_| Error::Keystore(format!("wallet {} is corrupted", self.name)))?;
+        if keypair.pubkey().ne(&self.pubkey) {
+            return Err(Error::Keystore(format!(
+                "wallet {} is corrupted",
+                self.name
+            )));
+        }
+        Ok(keypair)
+    }
+}
+
+/// 钱包名只允许字母、数字、'-' 和 '_'，避免写到钱包目录之外
+pub fn validate_name(name: &str) -> Result<(), Error> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c.eq(&'-') || c.eq(&'_'));
+    if !valid {
+        return Err(Error::Keystore(format!(
+            "invalid wallet name {:?}: use letters, digits, '-' and '_'",
+            name
+        )));
+    }
+    Ok(())
+}
+
+fn wallet_path(name: &str) -> PathBuf {
+    config_path(KEYSTORE_DIR).join(format!("{}.json", name))
+}
+
+pub fn wallet_exists(name: &str) -> bool {
+    wallet_path(name).exists()
+}
+
+/// 保存加密钱包，不会覆盖同名钱包
+pub fn save_wallet(wallet: &EncryptedWallet) -> Result<(), Error> {
+    validate_name(&wallet.name)?;
+    let path = wallet_path(&wallet.name);
+    if path.exists() {
+        return Err(Error::Keystore(format!(
+            "wallet {} already exists",
+            wallet.name
+        )));
+    }
+    let data =
+        serde_json::to_string_pretty(wallet).map_err(|err| Error::Keystore(err.to_string()))?;
+    path.parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| write_private(&path, data.as_bytes()))
+        .map_err(|err| Error::Keystore(format!("failed to write {:?}: {}", path, err)))
+}
+
+pub fn load_wallet(name: &str) -> Result<EncryptedWallet, Error> {
+    validate_name(name)?;
+    let path = wallet_path(name);
+    let data = fs::read_to_string(&path)
+        .map_err(|_| Error::Keystore(format!("wallet {} not found", name)))?;
+    serde_json::from_str(&data)
+        .map_err(|err| Error::Keystore(format!("failed to parse {:?}: {}", path, err)))
+}
+
+/// 按名称排序列出所有钱包
+pub fn list_wallets() -> Vec<EncryptedWallet> {
+    let Ok(entries) = fs::read_dir(config_path(KEYSTORE_DIR)) else {
+        return vec![];
+    };
+    let mut wallets: Vec<EncryptedWallet> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().map_or(false, |ext| ext.eq("json")))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|data| serde_json::from_str(&data).ok())
+        .collect();
+    wallets.sort_by(|a, b| a.name.cmp(&b.name));
+    wallets
+}
+
+pub fn remove_wallet(name: &str) -> Result<(), Error> {
+    validate_name(name)?;
+    fs::remove_file(wallet_path(name))
+        .map_err(|_| Error::Keystore(format!("wallet {} not found", name)))
+}
+
+/// 解锁 --wallet 选择的钱包，口令优先取自环境变量，否则交互输入
+pub fn unlock_wallet(name: &str) -> Result<Keypair, Error> {
+    let unlocked = UNLOCKED.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(bytes) = unlocked.lock().unwrap().get(name) {
+        return Keypair::from_bytes(bytes).map_err(|err| Error::Keystore(err.to_string()));
+    }
+    let wallet = load_wallet(name)?;
+    let passphrase = match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => prompt_passphrase(&format!("Passphrase for wallet {}: ", name))?,
+    };
+    let keypair = wallet.decrypt(&passphrase)?;
+    unlocked
+        .lock()
+        .unwrap()
+        .insert(name.to_string(), keypair.to_bytes());
+    Ok(keypair)
+}
+
+/// 用同一个口令解锁多个钱包（如 --all-wallets），口令只输入一次，已解锁的钱包不再解密
+pub fn unlock_wallets(wallets: &[EncryptedWallet]) -> Result<Vec<Keypair>, Error> {
+    let unlocked = UNLOCKED.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut passphrase: Option<String> = None;
+    let mut keypairs = vec![];
+    for wallet in wallets.iter() {
+        if let Some(bytes) = unlocked.lock().unwrap().get(&wallet.name) {
+            keypairs
+                .push(Keypair::from_bytes(bytes).map_err(|err| Error::Keystore(err.to_string()))?);
+            continue;
+        }
+        if passphrase.is_none() {
+            passphrase = Some(match std::env::var(PASSPHRASE_ENV) {
+                Ok(passphrase) => passphrase,
+                Err(_) => {
+                    prompt_passphrase(&format!("Passphrase for {} wallets: ", wallets.len()))?
+                }
+            });
+        }
+        let keypair = wallet
+            .decrypt(passphrase.as_deref().unwrap_or_default())
+            .map_err(|err| match err {
+                Error::Keystore(msg) => Error::Keystore(format!("wallet {}: {}", wallet.name, msg)),
+                err => err,
+            })?;
+        unlocked
+            .lock()
+            .unwrap()
+            .insert(wallet.name.clone(), keypair.to_bytes());
+        keypairs.push(keypair);
+    }
+    Ok(keypairs)
+}
+
+impl Miner {
+    /// 按 --wallet、--derivation-path 或 prompt:// 形式的 --keypair 加载本地签名密钥，需要签名的命令在开始时调用
+    pub fn select_signer(&self, args: &WalletSelectorArgs) -> Result<(), Error> {
+        let keypair = match (&args.wallet, &args.derivation_path) {
+            (Some(name), _) => unlock_wallet(name)?,
+            (None, Some(path)) => load_keypair(
+                self.keypair_filepath
+                    .as_deref()
+                    .unwrap_or(SEED_PHRASE_PROMPT),
+                Some(path),
+            )?,
+            (None, None) => match self.keypair_filepath.as_deref() {
+                Some(source) if is_seed_phrase_prompt(source) => load_keypair(source, None)?,
+                _ => return Ok(()),
+            },
+        };
+        SELECTED_SIGNER.get_or_init(|| keypair.to_bytes());
+        Ok(())
+    }
+
+    /// 本地签名密钥：--wallet 或 --derivation-path 选择的密钥，否则为 --keypair 指定的密钥文件
+    pub fn local_signer(&self) -> Keypair {
+        match SELECTED_SIGNER.get() {
+            Some(bytes) => Keypair::from_bytes(bytes).expect("Failed to load selected signer"),
+            None => self.signer(),
+        }
+    }
+}
+
+pub fn prompt_passphrase(prompt: &str) -> Result<String, Error> {
+    rpassword::prompt_password(prompt).map_err(|err| Error::Keystore(err.to_string()))
+}
+
+/// 为新钱包输入口令，需要输入两次确认
+pub fn prompt_new_passphrase() -> Result<String, Error> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        if passphrase.is_empty() {
+            return Err(Error::Keystore(format!(
+                "{} must not be empty",
+                PASSPHRASE_ENV
+            )));
+        }
+        return Ok(passphrase);
+    }
+    let passphrase = prompt_passphrase("New passphrase: ")?;
+    if passphrase.is_empty() {
+        return Err(Error::Keystore("passphrase must not be empty".to_string()));
+    }
+    if prompt_passphrase("Confirm passphrase: ")?.ne(&passphrase) {
+        return Err(Error::Keystore("passphrases do not match".to_string()));
+    }
+    Ok(passphrase)
+}
+
+fn cipher(passphrase: &str, salt: &[u8], kdf: KdfParams) -> Result<Aes256Gcm, Error> {
+    let params = Params::new(kdf.log_n, kdf.r, kdf.p, 32)
+        .map_err(|err| Error::Keystore(format!("invalid kdf params: {}", err)))?;
+    let mut key = [0u8; 32];
+    scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+        .map_err(|err| Error::Keystore(err.to_string()))?;
+    Aes256Gcm::new_from_slice(&key).map_err(|err| Error::Keystore(err.to_string()))
+}
+
+fn decode(data: &str) -> Result<Vec<u8>, Error> {
+    bs58::decode(data)
+        .into_vec()
+        .map_err(|err| Error::Keystore(err.to_string()))
+}
+
+// 钱包文件只允许当前用户读写
+#[cfg(unix)]
+fn write_private(path: &PathBuf, data: &[u8]) -> std::io::Result<()> {
+    use std::{io::Write, os::unix::fs::OpenOptionsExt};
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(data)
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &PathBuf, data: &[u8]) -> std::io::Result<()> {
+    fs::write(path, data)
+}
@@ -1,6 +1,10 @@
 use std::{
-    sync::{Arc, RwLock},
-    time::Instant,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
     usize,
 };
 
@@ -10,56 +14,162 @@ use drillx::{
     Hash, Solution,
 };
 use ore_api::{
-    consts::{BUS_ADDRESSES, BUS_COUNT, EPOCH_DURATION},
+    consts::{BUS_ADDRESSES, BUS_COUNT, EPOCH_DURATION, TOKEN_DECIMALS},
     state::{Bus, Config},
 };
 use ore_utils::AccountDeserialize;
 use rand::Rng;
-use solana_program::pubkey::Pubkey;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_program::{native_token::lamports_to_sol, pubkey::Pubkey};
 use solana_rpc_client::spinner;
-use solana_sdk::signer::Signer;
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature, signer::Signer};
+use solana_transaction_status::UiTransactionEncoding;
+use spl_token::amount_to_ui_amount;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 
 use crate::{
-    args::MineArgs,
+    args::{BudgetAction, MineArgs},
     error::Error,
-    pool::Pool,
+    fee_payers::configure_fee_payers,
+    nonce_range::{member_range, NonceRange},
+    output::machine_output,
+    pool::{any_healthy, first_healthy, Pool},
+    pool_history::{record_pool_round, PoolRound},
+    remote_signer::connect_remote_signer,
     send_and_confirm::ComputeBudget,
+    spend_budget::SpendBudget,
+    top_up::{configure_top_up, top_up_fees},
     utils::{
-        amount_u64_to_string, get_clock, get_config, get_updated_proof_with_authority, proof_pubkey,
+        amount_u64_to_string, get_clock, get_config, get_proof_with_authority,
+        get_updated_proof_with_authority, proof_pubkey,
     },
     Miner,
 };
 
+// 每个签名的基础交易费用（lamports）
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+// 交易发送后多久仍未上链视为不会再上链，不再查询其手续费（秒）
+const FEE_SETTLE_TIMEOUT: u64 = 120;
+
+// 预算用尽时暂停后重新检查的间隔（秒）
+const BUDGET_PAUSE_DELAY: u64 = 60;
+
+// 矿池不可用或出错后重新选择矿池的初始间隔（秒），连续失败时翻倍
+const POOL_FAILOVER_DELAY: u64 = 10;
+
+// 重新选择矿池的最大间隔（秒）
+const POOL_FAILOVER_MAX_DELAY: u64 = 300;
+
+// 在矿池上挖矿超过这个时间（秒）后出错，视为新的故障，重新从初始间隔开始退避
+const POOL_FAILOVER_RESET: u64 = 600;
+
+// 向矿池提交改进解决方案的最小间隔（毫秒）
+const POOL_SUBMIT_INTERVAL: u64 = 1_000;
+
 impl Miner {
     // 定义一个公共的异步函数 `mine`，用于处理矿工的不同挖掘模式（池挖矿或单人挖矿）。
     pub async fn mine(&self, args: MineArgs) -> Result<(), Error> {
-        // 使用 `match` 语句处理 `args.pool_url` 的 `Some` 和 `None` 两种情况。
-        match args.pool_url {
-            // 当 `args.pool_url` 为 `Some` 时，表示用户指定了矿池 URL。
-            Some(ref pool_url) => {
-                // 创建一个 `Pool` 结构体实例，包含 HTTP 客户端和矿池 URL。
-                let pool = &Pool {
-                    http_client: reqwest::Client::new(), // 创建一个新的HTTP客户端
-                    pool_url: pool_url.clone(), // 复制矿池URL
-                };
-                // 调用 `mine_pool` 异步方法，并等待其完成。
-                // 使用 `?` 操作符处理可能产生的错误，并将错误向上抛出。
-                self.mine_pool(args, pool).await?;
-            }
-            // 当 `args.pool_url` 为 `None` 时，表示用户选择单人挖矿。
-            None => {
-                // 调用 `mine_solo` 异步方法，并等待其完成。
-                self.mine_solo(args).await;
-            }
+        self.select_signer(&args.signer)?;
+        if let Some(ref url) = args.remote.remote_signer {
+            connect_remote_signer(url).await?;
+        }
+        configure_fee_payers(&args.fees.fee_payers, args.fees.fee_payer_strategy)?;
+        configure_top_up(&args.top_up)?;
+        // 没有指定矿池 URL 时，表示用户选择单人挖矿。
+        if args.pool_url.is_empty() {
+            self.mine_solo(args).await?;
+        } else {
+            self.mine_pools(args).await;
         }
         // 返回 `Ok(())`，表示函数成功执行。
         Ok(())
     }
 
-    async fn mine_solo(&self, args: MineArgs) {
-        // 如果需要，打开账户
-        let signer = self.signer();
-        self.open().await;
+    // 按顺序在多个矿池之间故障转移：当前矿池出错时切换到下一个健康的矿池，
+    // 排在前面的矿池恢复后切换回去
+    async fn mine_pools(&self, args: MineArgs) {
+        let pools: Vec<Pool> = args
+            .pool_url
+            .iter()
+            .map(|pool_url| Pool::new(pool_url.clone()))
+            .collect();
+        let mut start = 0;
+        let mut delay = POOL_FAILOVER_DELAY;
+        loop {
+            match first_healthy(&pools, start).await {
+                Some(i) => {
+                    let pool = &pools[i];
+                    println!("Mining with pool {}", pool.pool_url);
+                    let started = Instant::now();
+                    let result = self.mine_pool(&args, pool, &pools[..i]).await;
+                    if started.elapsed().as_secs().ge(&POOL_FAILOVER_RESET) {
+                        delay = POOL_FAILOVER_DELAY;
+                    }
+                    match result {
+                        // 排在前面的矿池恢复可用，立即从头重新选择
+                        Ok(()) => {
+                            start = 0;
+                            continue;
+                        }
+                        // 从下一个矿池开始重新选择
+                        Err(err) => {
+                            println!(
+                                "{} Pool {} failed: {}. Retrying in {} sec...",
+                                "WARNING".bold().yellow(),
+                                pool.pool_url,
+                                err,
+                                delay
+                            );
+                            start = (i + 1) % pools.len();
+                        }
+                    }
+                }
+                // 所有矿池都不可用
+                None if args.solo_fallback => {
+                    println!(
+                        "{} No pool is reachable. Falling back to solo mining...",
+                        "WARNING".bold().yellow()
+                    );
+                    return self.mine_solo(args).await;
+                }
+                None => {
+                    println!(
+                        "{} No pool is reachable. Retrying in {} sec...",
+                        "WARNING".bold().yellow(),
+                        delay
+                    );
+                    start = 0;
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(delay)).await;
+            delay = delay.saturating_mul(2).min(POOL_FAILOVER_MAX_DELAY);
+        }
+    }
+
+    async fn mine_solo(&self, args: MineArgs) -> Result<(), Error> {
+        // 如果需要，打开账户；使用委托的矿工密钥时证明必须已由 authority 打开
+        let authority = match args.authority {
+            Some(ref authority) => Pubkey::from_str(authority)
+                .map_err(|_| Error::Internal("invalid authority address".to_string()))?,
+            None => {
+                self.open().await;
+                self.authority()
+            }
+        };
+
+        // 只有证明登记的矿工密钥可以提交解决方案
+        let miner = get_proof_with_authority(&self.rpc_client, authority)
+            .await
+            .miner;
+        if miner.ne(&self.local_signer().pubkey()) && miner.ne(&self.authority()) {
+            return Err(Error::Internal(format!(
+                "the proof of {} can only be mined by {}. Use update-miner to delegate it to {}.",
+                authority,
+                miner,
+                self.local_signer().pubkey()
+            )));
+        }
 
         // 检查线程数
         self.check_num_cores(args.cores);
@@ -67,12 +177,38 @@ impl Miner {
         // 开始循环挖矿
         let mut last_hash_at = 0;
         let mut last_balance = 0;
+        let mut skipped: Option<SkippedRound> = None;
+        let mut budget = SpendBudget::from_args(&args);
+        let mut unsettled: Vec<(Signature, Instant)> = vec![];
         loop {
+            // 如果 SOL 预算已用尽并配置为暂停，则等待花费回落到预算以内
+            if let Some(ref budget) = budget {
+                while budget.exhausted() && budget.action.eq(&BudgetAction::Pause) {
+                    println!(
+                        "{} SOL budget exhausted. Pausing for {} sec...",
+                        "WARNING".bold().yellow(),
+                        BUDGET_PAUSE_DELAY
+                    );
+                    tokio::time::sleep(Duration::from_secs(BUDGET_PAUSE_DELAY)).await;
+                }
+            }
+
             // 获取工作量证明
             let config = get_config(&self.rpc_client).await;
             println!("config.................: {:?}", config);
             // 打印当前状态信息
-            let proof = get_updated_proof_with_authority(&self.rpc_client, signer.pubkey(), last_hash_at).await;
+            // 如果上一轮被跳过，挑战不会改变，直接获取当前证明
+            let proof = match skipped {
+                Some(_) => get_proof_with_authority(&self.rpc_client, authority).await,
+                None => {
+                    get_updated_proof_with_authority(
+                        &self.rpc_client,
+                        authority,
+                        last_hash_at,
+                    )
+                    .await
+                }
+            };
             println!(
                 "\n\nStake: {} ORE\n{}  Multiplier: {:12}x",
                 amount_u64_to_string(proof.balance),
@@ -90,66 +226,220 @@ impl Miner {
             last_hash_at = proof.last_hash_at;
             last_balance = proof.balance;
 
-            // 计算截止时间
+            // 计算截止时间，跳过的轮次仍以证明的 last_hash_at 为准
             let cutoff_time = self.get_cutoff(proof.last_hash_at, args.buffer_time).await;
 
-            // 构建Nonce索引
-            let mut nonce_indices = Vec::with_capacity(args.cores as usize);
-            for n in 0..(args.cores) {
-                let nonce = u64::MAX.saturating_div(args.cores).saturating_mul(n);
-                nonce_indices.push(nonce);
-            }
+            // 为每个核心分配 nonce 区间；跳过的轮次从上次停止的 nonce 继续，
+            // 并且只接受比上次最佳解决方案更难的解决方案
+            let (nonce_ranges, min_difficulty) = match skipped {
+                Some(ref skipped) => (
+                    skipped.remaining.clone(),
+                    (config.min_difficulty as u32).max(skipped.difficulty.saturating_add(1)),
+                ),
+                None => (
+                    NonceRange::FULL.split(args.cores),
+                    config.min_difficulty as u32,
+                ),
+            };
 
             // 运行挖矿算法
-            let solution = Self::find_hash_par(
+            let (solution, remaining) = Self::find_hash_par(
                 proof.challenge,
                 cutoff_time,
                 args.cores,
-                config.min_difficulty as u32,
-                nonce_indices.as_slice(),
+                min_difficulty,
+                nonce_ranges.as_slice(),
+                None,
             )
                 .await;
 
+            // 保留跳过的轮次中更好的解决方案
+            let solution = match skipped.take() {
+                Some(skipped)
+                    if skipped.difficulty.gt(&solution.to_hash().difficulty()) =>
+                {
+                    skipped.solution
+                }
+                _ => solution,
+            };
+
             // 构建指令集
-            let mut ixs = vec![ore_api::instruction::auth(proof_pubkey(signer.pubkey()))];
+            let mut ixs = vec![ore_api::instruction::auth(proof_pubkey(authority))];
             let mut compute_budget = 500_000;
 
             // 根据条件增加计算预算并添加重置指令
             if self.should_reset(config).await && rand::thread_rng().gen_range(0..100).eq(&0) {
                 compute_budget += 100_000;
-                ixs.push(ore_api::instruction::reset(signer.pubkey()));
+                ixs.push(ore_api::instruction::reset(miner));
             }
 
             // 构建挖矿指令
             ixs.push(ore_api::instruction::mine(
-                signer.pubkey(),
-                signer.pubkey(),
+                miner,
+                authority,
                 self.find_bus().await,
                 solution,
             ));
 
-            // 提交交易
-            self.send_and_confirm(&ixs, ComputeBudget::Fixed(compute_budget), false)
+            // 根据 SOL 预算限制优先费用
+            let max_fee = budget
+                .as_ref()
+                .and_then(|budget| self.budget_fee_cap(budget, miner, compute_budget));
+
+            // 如果预期奖励不足以覆盖交易费用（按实际会使用的优先费用计算），则跳过本轮提交
+            if let Some(min_profit_ratio) = args.min_profit_ratio {
+                let multiplier = calculate_multiplier(proof.balance, config.top_balance);
+                let reward = expected_reward(&config, solution.to_hash().difficulty(), multiplier);
+                let cost = self.transaction_cost(miner, compute_budget, max_fee).await;
+                let ratio = profit_ratio(reward, cost, args.ore_price.unwrap_or(0.0));
+                if ratio.lt(&min_profit_ratio) {
+                    println!(
+                        "{} Skipping submission: expected reward {} ORE, cost {} SOL (ratio {:.2} < {:.2})",
+                        "WARNING".bold().yellow(),
+                        amount_u64_to_string(reward),
+                        lamports_to_sol(cost),
+                        ratio,
+                        min_profit_ratio
+                    );
+                    skipped = Some(SkippedRound {
+                        solution,
+                        difficulty: solution.to_hash().difficulty(),
+                        remaining,
+                    });
+                    continue;
+                }
+            }
+            let top_up_fees_before = top_up_fees();
+
+            // 提交交易，记录每次签名得到的交易签名
+            let mut sent = vec![];
+            self.send_and_confirm_tracked(
+                &ixs,
+                ComputeBudget::Fixed(compute_budget),
+                false,
+                max_fee,
+                &[],
+                &mut sent,
+            )
+            .await
+            .ok();
+
+            // 按上链交易的实际手续费记录本轮花费（包括失败的交易），尚未上链的交易在之后的轮次中继续查询；
+            // 自动补充的交易由资金钱包支付，按其手续费计入
+            if let Some(budget) = budget.as_mut() {
+                let now = Instant::now();
+                unsettled.extend(sent.into_iter().map(|signature| (signature, now)));
+                let fees = self.settle_fees(&mut unsettled).await;
+                budget.record(
+                    fees.saturating_add(top_up_fees().saturating_sub(top_up_fees_before)),
+                );
+            }
+        }
+    }
+
+    // 查询已发送交易的实际手续费（lamports），返回已上链交易的手续费之和并从 unsettled 中移除；
+    // 区块哈希过期后仍未上链的交易不会再上链，直接移除
+    async fn settle_fees(&self, unsettled: &mut Vec<(Signature, Instant)>) -> u64 {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        let mut fees = 0u64;
+        let mut pending = vec![];
+        for (signature, sent_at) in unsettled.drain(..) {
+            match self
+                .rpc_client
+                .get_transaction_with_config(&signature, config)
                 .await
-                .ok();
+            {
+                Ok(tx) => {
+                    let fee = tx.transaction.meta.map_or(0, |meta| meta.fee);
+                    fees = fees.saturating_add(fee);
+                }
+                Err(_) if sent_at.elapsed().as_secs().lt(&FEE_SETTLE_TIMEOUT) => {
+                    pending.push((signature, sent_at));
+                }
+                Err(_) => {}
+            }
         }
+        *unsettled = pending;
+        fees
     }
 
-    async fn mine_pool(&self, args: MineArgs, pool: &Pool) -> Result<(), Error> {
-        // 注册矿池成员(如果需要)
-        let mut pool_member = pool.post_pool_register(self).await?;
-        // 获取矿池成员的索引
-        let nonce_index = pool_member.id as u64;
+    // 根据预算计算本轮允许的最高优先费用（microlamports）
+    fn budget_fee_cap(
+        &self,
+        budget: &SpendBudget,
+        miner: Pubkey,
+        compute_budget: u32,
+    ) -> Option<u64> {
+        if budget.exhausted() && budget.action.eq(&BudgetAction::ZeroFee) {
+            return Some(0);
+        }
+        budget.round_limit().map(|limit| {
+            limit
+                .saturating_sub(self.signature_fees(miner))
+                .saturating_mul(1_000_000)
+                .saturating_div(compute_budget.max(1) as u64)
+        })
+    }
+
+    // 挖矿交易的签名费用（lamports）：由提交解决方案的矿工密钥签名，
+    // 只有所有候选费用支付者都是该密钥时才只需一个签名
+    fn signature_fees(&self, miner: Pubkey) -> u64 {
+        let signatures = if self.fee_payer_pubkeys().iter().all(|payer| payer.eq(&miner)) {
+            1
+        } else {
+            2
+        };
+        LAMPORTS_PER_SIGNATURE.saturating_mul(signatures)
+    }
+
+    // 估算一笔挖矿交易的费用（lamports），包括签名费用和优先费用；优先费用不超过预算上限 max_fee
+    async fn transaction_cost(
+        &self,
+        miner: Pubkey,
+        compute_budget: u32,
+        max_fee: Option<u64>,
+    ) -> u64 {
+        let priority_fee = self
+            .current_priority_fee()
+            .await
+            .min(max_fee.unwrap_or(u64::MAX));
+        self.signature_fees(miner)
+            .saturating_add(priority_fee.saturating_mul(compute_budget as u64) / 1_000_000)
+    }
+
+    // 在矿池上挖矿直到出错；preferred 中排在前面的矿池恢复可用时返回 Ok，以便切换回去
+    async fn mine_pool(
+        &self,
+        args: &MineArgs,
+        pool: &Pool,
+        preferred: &[Pool],
+    ) -> Result<(), Error> {
         // 获取链上的矿池账户信息
         let pool_address = pool.get_pool_address().await?;
-        // 初始化链上矿池成员的状态
-        let mut pool_member_onchain: ore_pool_api::state::Member;
+        // 读取矿池运营商，用于校验带签名的响应
+        pool.require_pool_operator(
+            self,
+            pool_address.address,
+            &args.pool_operator,
+            args.insecure_pool,
+        )
+        .await?;
+        // 注册矿池成员(如果需要)
+        let mut pool_member = pool.post_pool_register(&self.local_signer()).await?;
         // 检查线程数
         self.check_num_cores(args.cores);
         // 开始循环挖矿
         let mut last_hash_at = 0;
         let mut last_balance: i64;
         loop {
+            if any_healthy(preferred).await {
+                println!("A preferred pool is reachable again. Switching back...");
+                return Ok(());
+            }
             // 获取最新的挑战信息
             let member_challenge = pool.get_updated_pool_challenge(last_hash_at).await?;
             // 更新上次的余额和哈希值
@@ -157,38 +447,73 @@ impl Miner {
             last_hash_at = member_challenge.challenge.lash_hash_at;
             // 计算截止时间
             let cutoff_time = self.get_cutoff(last_hash_at, member_challenge.buffer).await;
-            // 构建Nonce索引
-            let num_total_members = member_challenge.num_total_members.max(1);
-            let u64_unit = u64::MAX.saturating_div(num_total_members);
-            let left_bound = u64_unit.saturating_mul(nonce_index);
-            let range_per_core = u64_unit.saturating_div(args.cores);
-            let mut nonce_indices = Vec::with_capacity(args.cores as usize);
-            for n in 0..(args.cores) {
-                let index = left_bound + n * range_per_core;
-                nonce_indices.push(index);
+            // 获取成员的 nonce 区间，并为每个核心分配互不重叠的子区间
+            let Some(range) = member_range(
+                pool_member.nonce_range,
+                pool_member.id,
+                member_challenge.num_total_members,
+            ) else {
+                // 刚注册的成员尚未计入本轮，等待下一轮再开始挖矿
+                println!(
+                    "{} Member {} has no nonce range in this round. Waiting for the next challenge...",
+                    "WARNING".bold().yellow(),
+                    pool_member.id
+                );
+                continue;
+            };
+            let nonce_ranges = range.split(args.cores);
+            // 运行挖矿算法，同时将每个新的最佳解决方案限速提交给矿池运营商
+            let (sender, receiver) = mpsc::unbounded_channel();
+            let ((solution, _), submitted) = tokio::join!(
+                Self::find_hash_par(
+                    member_challenge.challenge.challenge,
+                    cutoff_time,
+                    args.cores,
+                    member_challenge.challenge.min_difficulty as u32,
+                    nonce_ranges.as_slice(),
+                    Some(sender),
+                ),
+                self.stream_pool_solutions(pool, receiver)
+            );
+            // 最终的解决方案比最后一次成功提交的更好（或本轮还没有成功提交过）时，提交最终的解决方案
+            let difficulty = solution.to_hash().difficulty();
+            let submitted = match submitted {
+                Some((last, Ok(()))) if last.ge(&difficulty) => Ok(()),
+                _ => pool.post_pool_solution(&self.local_signer(), &solution).await,
+            };
+            // 在本地记录本轮提交，供 pool status 核对矿池记入的余额
+            if let Err(err) = submitted {
+                record_pool_round(&PoolRound::new(
+                    &pool.pool_url,
+                    &pool_member.authority,
+                    last_hash_at,
+                    difficulty,
+                    false,
+                    pool_member.total_balance,
+                ));
+                return Err(err);
             }
-            // 运行挖矿算法
-            let solution = Self::find_hash_par(
-                member_challenge.challenge.challenge,
-                cutoff_time,
-                args.cores,
-                member_challenge.challenge.min_difficulty as u32,
-                nonce_indices.as_slice(),
-            )
-                .await;
-            // 向矿池运营商提交解决方案
-            pool.post_pool_solution(self, &solution).await?;
             // 获取更新后的矿池成员信息
-            pool_member = pool.get_pool_member(self).await?;
-            // 获取链上更新后的矿池成员信息
-            pool_member_onchain = pool
+            pool_member = pool.get_pool_member(&self.local_signer()).await?;
+            record_pool_round(&PoolRound::new(
+                &pool.pool_url,
+                &pool_member.authority,
+                last_hash_at,
+                difficulty,
+                true,
+                pool_member.total_balance,
+            ));
+            // 获取链上更新后的矿池成员信息（只在链下记账的矿池没有链上成员账户）
+            if let Ok(pool_member_onchain) = pool
                 .get_pool_member_onchain(self, pool_address.address)
-                .await?;
-            // 打印进度信息
-            println!(
-                "Claimable ORE balance: {}",
-                amount_u64_to_string(pool_member_onchain.balance)
-            );
+                .await
+            {
+                // 打印进度信息
+                println!(
+                    "Claimable ORE balance: {}",
+                    amount_u64_to_string(pool_member_onchain.balance)
+                );
+            }
             if last_hash_at.gt(&0) {
                 println!(
                     "Change of ORE credits in pool: {}",
@@ -200,6 +525,30 @@ impl Miner {
         }
     }
 
+    // 将挖矿过程中找到的改进解决方案限速提交给矿池，返回最后一次提交的难度和结果
+    async fn stream_pool_solutions(
+        &self,
+        pool: &Pool,
+        mut receiver: UnboundedReceiver<Solution>,
+    ) -> Option<(u32, Result<(), Error>)> {
+        let mut result = None;
+        let mut last_submit: Option<Instant> = None;
+        while let Some(mut solution) = receiver.recv().await {
+            if let Some(last_submit) = last_submit {
+                let next_submit = last_submit + Duration::from_millis(POOL_SUBMIT_INTERVAL);
+                tokio::time::sleep_until(next_submit.into()).await;
+            }
+            // 只提交等待期间收到的最新（最佳）解决方案
+            while let Ok(latest) = receiver.try_recv() {
+                solution = latest;
+            }
+            last_submit = Some(Instant::now());
+            let difficulty = solution.to_hash().difficulty();
+            result = Some((difficulty, pool.post_pool_solution(&self.local_signer(), &solution).await));
+        }
+        result
+    }
+
     /*
      * 实现了一个并行挖矿函数 find_hash_par，用于寻找满足特定难度的哈希值
      */
@@ -208,12 +557,13 @@ impl Miner {
         cutoff_time: u64, // 挖矿截止时间（秒）
         cores: u64, // 可用核心线程数
         min_difficulty: u32, // 最小挖矿难度要求
-        nonce_indices: &[u64], // 非随机书索引列表
-    ) -> Solution {
+        nonce_ranges: &[NonceRange], // 每个核心的 nonce 区间
+        improvements: Option<UnboundedSender<Solution>>, // 接收每个达到最小难度的新全局最佳解决方案
+    ) -> (Solution, Vec<NonceRange>) { // 最佳解决方案，以及每个核心尚未计算的 nonce 区间
         // 创建一个可在线程间共享的进度条
         let progress_bar = Arc::new(spinner::new_progress_bar());
-        // 创建一个可在线程间共享的读写锁，用于记录全局最佳难度
-        let global_best_difficulty = Arc::new(RwLock::new(0u32));
+        // 创建一个可在线程间共享的原子变量，用于记录全局最佳难度
+        let global_best_difficulty = Arc::new(AtomicU32::new(0));
         // 设置初始进度条消息
         progress_bar.set_message("Mining...");
         // 获取系统中的所有核心 ID，并过滤出指定数量的核心
@@ -223,9 +573,10 @@ impl Miner {
         let handles: Vec<_> = core_ids
             .map(|i| {
                 let global_best_difficulty = Arc::clone(&global_best_difficulty);
+                let improvements = improvements.clone();
                 std::thread::spawn({
                     let progress_bar = progress_bar.clone();
-                    let nonce = nonce_indices[i.id];
+                    let range = nonce_ranges[i.id];
                     let mut memory = equix::SolverMemory::new();
                     move || {
                         // 将当前线程绑定到指定核心
@@ -233,10 +584,13 @@ impl Miner {
 
                         // 开始哈希计算
                         let timer = Instant::now();
-                        let mut nonce = nonce;
+                        let mut nonce = range.start;
                         let mut best_nonce = nonce;
                         let mut best_difficulty = 0;
                         let mut best_hash = Hash::default();
+                        if range.is_empty() {
+                            return (i.id, nonce, best_nonce, best_difficulty, best_hash);
+                        }
                         loop {
                             // 计算哈希值
                             let hxs = drillx::hashes_with_memory(
@@ -252,9 +606,17 @@ impl Miner {
                                     best_nonce = nonce;
                                     best_difficulty = difficulty;
                                     best_hash = hx;
-                                    if best_difficulty.gt(&*global_best_difficulty.read().unwrap())
-                                    {
-                                        *global_best_difficulty.write().unwrap() = best_difficulty;
+                                    // fetch_max 原子地刷新全局最佳难度，只有真正刷新了的线程才提交
+                                    let global_best = global_best_difficulty
+                                        .fetch_max(best_difficulty, Ordering::Relaxed);
+                                    if best_difficulty.gt(&global_best) {
+                                        if let Some(ref improvements) = improvements {
+                                            if best_difficulty.ge(&min_difficulty) {
+                                                improvements
+                                                    .send(Solution::new(hx.d, nonce.to_le_bytes()))
+                                                    .ok();
+                                            }
+                                        }
                                     }
                                 }
                             }
@@ -262,7 +624,7 @@ impl Miner {
                             // 如果达到截止时间，则退出循环
                             if nonce % 100 == 0 {
                                 let global_best_difficulty =
-                                    *global_best_difficulty.read().unwrap();
+                                    global_best_difficulty.load(Ordering::Relaxed);
                                 if timer.elapsed().as_secs().ge(&cutoff_time) {
                                     if i.id == 0 {
                                         progress_bar.set_message(format!(
@@ -272,6 +634,7 @@ impl Miner {
                                     }
                                     if global_best_difficulty.ge(&min_difficulty) {
                                         // Mine until min difficulty has been met
+                                        nonce += 1;
                                         break;
                                     }
                                 } else if i.id == 0 {
@@ -286,28 +649,44 @@ impl Miner {
                                 }
                             }
 
-                            // 增加非随机数
+                            // 增加非随机数，用尽分配的区间后停止
                             nonce += 1;
+                            if nonce.ge(&range.end) {
+                                break;
+                            }
                         }
 
-                        // 返回最佳非随机数及其哈希值
-                        (best_nonce, best_difficulty, best_hash)
+                        // 返回下一个未计算的非随机数、最佳非随机数及其哈希值
+                        (i.id, nonce, best_nonce, best_difficulty, best_hash)
                     }
                 })
             })
             .collect();
 
-        // 等待所有线程完成，并返回最佳非随机数
+        // 释放发送端，所有线程结束后接收端随之关闭
+        drop(improvements);
+
+        // 等待所有线程完成（不阻塞异步运行时），并返回最佳非随机数
+        let results = tokio::task::spawn_blocking(move || {
+            handles
+                .into_iter()
+                .filter_map(|h| h.join().ok())
+                .collect::<Vec<_>>()
+        })
+        .await
+        .unwrap_or_default();
         let mut best_nonce = 0;
         let mut best_difficulty = 0;
         let mut best_hash = Hash::default();
-        for h in handles {
-            if let Ok((nonce, difficulty, hash)) = h.join() {
-                if difficulty > best_difficulty {
-                    best_difficulty = difficulty;
-                    best_nonce = nonce;
-                    best_hash = hash;
-                }
+        let mut remaining = nonce_ranges.to_vec();
+        for (id, next, nonce, difficulty, hash) in results {
+            if let Some(range) = remaining.get_mut(id) {
+                range.start = next.min(range.end);
+            }
+            if difficulty > best_difficulty {
+                best_difficulty = difficulty;
+                best_nonce = nonce;
+                best_hash = hash;
             }
         }
 
@@ -318,19 +697,30 @@ impl Miner {
             best_difficulty
         ));
 
-        Solution::new(best_hash.d, best_nonce.to_le_bytes())
+        (
+            Solution::new(best_hash.d, best_nonce.to_le_bytes()),
+            remaining,
+        )
     }
 
     pub fn check_num_cores(&self, cores: u64) {
         let num_cores = num_cpus::get() as u64;
         if cores.gt(&num_cores) {
-            println!("{} Cannot exceeds available cores ({})", "WARNING".bold().yellow(),
+            // 机器可读输出时诊断信息写到 stderr，不混入 stdout 的数据
+            let warning = format!(
+                "{} Cannot exceeds available cores ({})",
+                "WARNING".bold().yellow(),
                 num_cores
             );
+            if machine_output().is_some() {
+                eprintln!("{}", warning);
+            } else {
+                println!("{}", warning);
+            }
         }
     }
 
-    async fn should_reset(&self, config: Config) -> bool {
+    pub(crate) async fn should_reset(&self, config: Config) -> bool {
         let clock = get_clock(&self.rpc_client).await;
         config
             .last_reset_at
@@ -339,7 +729,7 @@ impl Miner {
             .le(&clock.unix_timestamp)
     }
 
-    async fn get_cutoff(&self, last_hash_at: i64, buffer_time: u64) -> u64 {
+    pub(crate) async fn get_cutoff(&self, last_hash_at: i64, buffer_time: u64) -> u64 {
         let clock = get_clock(&self.rpc_client).await;
         last_hash_at
             .saturating_add(60)
@@ -348,7 +738,7 @@ impl Miner {
             .max(0) as u64
     }
 
-    async fn find_bus(&self) -> Pubkey {
+    pub(crate) async fn find_bus(&self) -> Pubkey {
         // Fetch the bus with the largest balance
         if let Ok(accounts) = self.rpc_client.get_multiple_accounts(&BUS_ADDRESSES).await {
             let mut top_bus_balance: u64 = 0;
@@ -372,10 +762,39 @@ impl Miner {
     }
 }
 
-fn calculate_multiplier(balance: u64, top_balance: u64) -> f64 {
+// 因收益不足而跳过提交的轮次：挑战不变，下一次从停止处继续寻找更好的解决方案
+struct SkippedRound {
+    solution: Solution,
+    difficulty: u32,
+    remaining: Vec<NonceRange>,
+}
+
+pub(crate) fn calculate_multiplier(balance: u64, top_balance: u64) -> f64 {
     1.0 + (balance as f64 / top_balance as f64).min(1.0f64)
 }
 
+// 与 rewards 中的奖励表一致：base_reward_rate * 2^(difficulty - min_difficulty)，再乘以质押倍数
+fn expected_reward(config: &Config, difficulty: u32, multiplier: f64) -> u64 {
+    let min_difficulty = config.min_difficulty as u32;
+    if difficulty.lt(&min_difficulty) {
+        return 0;
+    }
+    let reward_rate = config
+        .base_reward_rate
+        .saturating_mul(2u64.saturating_pow(difficulty - min_difficulty));
+    (reward_rate as f64 * multiplier) as u64
+}
+
+// 以 SOL 计价的预期奖励与交易费用之比
+fn profit_ratio(reward: u64, cost: u64, ore_price: f64) -> f64 {
+    let reward_sol = amount_to_ui_amount(reward, TOKEN_DECIMALS) * ore_price;
+    let cost_sol = lamports_to_sol(cost);
+    if cost_sol.eq(&0.0) {
+        return f64::INFINITY;
+    }
+    reward_sol / cost_sol
+}
+
 fn format_duration(seconds: u32) -> String {
     let minutes = seconds / 60;
     let remaining_seconds = seconds % 60;
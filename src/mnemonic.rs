@@ -0,0 +1,184 @@
+use bip39::{Language, Mnemonic, MnemonicType, Seed};
+use solana_sdk::{
+    derivation_path::DerivationPath,
+    signature::{read_keypair_file, Keypair, Signer},
+    signer::keypair::keypair_from_seed_and_derivation_path,
+};
+
+use crate::{error::Error, keystore::prompt_passphrase};
+
+/// Solana 钱包的标准派生路径中的第一个账户
+pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/501'/0'/0'";
+
+/// 以此作为密钥路径时，改为交互输入助记词并按派生路径生成密钥对
+///
+/// 可以在来源中指定派生路径：prompt://?key=1/0 表示 m/44'/501'/1'/0'，
+/// prompt://?full-path=m/44'/501'/1'/0' 为完整路径。
+pub const SEED_PHRASE_PROMPT: &str = "prompt://";
+
+/// 生成指定单词数（12、15、18、21 或 24）的英文助记词
+pub fn generate_mnemonic(words: usize) -> Result<Mnemonic, Error> {
+    let mnemonic_type = MnemonicType::for_word_count(words)
+        .map_err(|_| Error::Mnemonic(format!("unsupported word count {}", words)))?;
+    Ok(Mnemonic::new(mnemonic_type, Language::English))
+}
+
+pub fn parse_mnemonic(phrase: &str) -> Result<Mnemonic, Error> {
+    let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    Mnemonic::from_phrase(&phrase, Language::English)
+        .map_err(|err| Error::Mnemonic(format!("invalid seed phrase: {}", err)))
+}
+
+/// 解析形如 m/44'/501'/0'/0' 的派生路径，缺省时使用第一个账户
+pub fn parse_derivation_path(path: Option<&str>) -> Result<DerivationPath, Error> {
+    DerivationPath::from_absolute_path_str(path.unwrap_or(DEFAULT_DERIVATION_PATH))
+        .map_err(|err| Error::Mnemonic(format!("invalid derivation path: {}", err)))
+}
+
+/// 第 index 个账户的标准派生路径 m/44'/501'/index'/0'
+pub fn account_derivation_path(index: u32) -> DerivationPath {
+    DerivationPath::new_bip44(Some(index), Some(0))
+}
+
+/// 从助记词和可选的 BIP39 口令按派生路径生成密钥对
+pub fn keypair_from_mnemonic(
+    mnemonic: &Mnemonic,
+    passphrase: &str,
+    derivation_path: &DerivationPath,
+) -> Result<Keypair, Error> {
+    let seed = Seed::new(mnemonic, passphrase);
+    keypair_from_seed_and_derivation_path(seed.as_bytes(), Some(derivation_path.clone()))
+        .map_err(|err| Error::Mnemonic(err.to_string()))
+}
+
+/// 交互输入助记词和可选的 BIP39 口令
+pub fn prompt_mnemonic() -> Result<(Mnemonic, String), Error> {
+    let mnemonic = parse_mnemonic(&prompt_passphrase("Seed phrase: ")?)?;
+    let passphrase = prompt_passphrase("BIP39 passphrase (empty for none): ")?;
+    Ok((mnemonic, passphrase))
+}
+
+/// 密钥来源是否为交互输入的助记词
+pub fn is_seed_phrase_prompt(source: &str) -> bool {
+    source.starts_with(SEED_PHRASE_PROMPT)
+}
+
+/// prompt:// 来源的派生路径：来源中指定的路径，否则为 derivation_path，都未指定时为第一个账户
+pub fn source_derivation_path(
+    source: &str,
+    derivation_path: Option<&str>,
+) -> Result<DerivationPath, Error> {
+    let query = source
+        .strip_prefix(SEED_PHRASE_PROMPT)
+        .unwrap_or_default()
+        .trim_start_matches('/');
+    if query.is_empty() {
+        return parse_derivation_path(derivation_path);
+    }
+    if derivation_path.is_some() {
+        return Err(Error::Mnemonic(format!(
+            "{} already sets a derivation path, drop --derivation-path",
+            source
+        )));
+    }
+    let path = match query.trim_start_matches('?').split_once('=') {
+        Some(("key", key)) => DerivationPath::from_key_str(key),
+        Some(("full-path", path)) => DerivationPath::from_absolute_path_str(path),
+        _ => {
+            return Err(Error::Mnemonic(format!(
+                "invalid seed phrase source {}, expected {}?key=ACCOUNT/CHANGE",
+                source, SEED_PHRASE_PROMPT
+            )))
+        }
+    };
+    path.map_err(|err| Error::Mnemonic(format!("invalid derivation path in {}: {}", source, err)))
+}
+
+/// 加载 signer 或 fee payer 的密钥对：密钥文件，或 prompt:// 表示按派生路径从助记词生成
+pub fn load_keypair(source: &str, derivation_path: Option<&str>) -> Result<Keypair, Error> {
+    if is_seed_phrase_prompt(source) {
+        let derivation_path = source_derivation_path(source, derivation_path)?;
+        let (mnemonic, passphrase) = prompt_mnemonic()?;
+        return keypair_from_mnemonic(&mnemonic, &passphrase, &derivation_path);
+    }
+    if derivation_path.is_some() {
+        return Err(Error::Mnemonic(format!(
+            "--derivation-path requires a seed phrase, use {} instead of a keypair file",
+            SEED_PHRASE_PROMPT
+        )));
+    }
+    read_keypair_file(source)
+        .map_err(|err| Error::Internal(format!("failed to read keypair {}: {}", source, err)))
+}
+
+/// 加载多个密钥对，所有 prompt:// 来源共用一次输入的助记词，各自按来源中的派生路径生成
+///
+/// 多个来源得到同一个密钥时返回错误，例如两个 prompt:// 来源都没有指定派生路径。
+pub fn load_keypairs(sources: &[String]) -> Result<Vec<Keypair>, Error> {
+    let mut mnemonic = None;
+    let mut keypairs: Vec<Keypair> = vec![];
+    for source in sources.iter() {
+        let keypair = if is_seed_phrase_prompt(source) {
+            let derivation_path = source_derivation_path(source, None)?;
+            if mnemonic.is_none() {
+                mnemonic = Some(prompt_mnemonic()?);
+            }
+            let (mnemonic, passphrase) = mnemonic.as_ref().unwrap();
+            keypair_from_mnemonic(mnemonic, passphrase, &derivation_path)?
+        } else {
+            load_keypair(source, None)?
+        };
+        if keypairs
+            .iter()
+            .any(|other| other.pubkey().eq(&keypair.pubkey()))
+        {
+            return Err(Error::Mnemonic(format!(
+                "{} resolves to {}, which is already loaded",
+                source,
+                keypair.pubkey()
+            )));
+        }
+        keypairs.push(keypair);
+    }
+    Ok(keypairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_sources_select_their_own_derivation_path() {
+        let path = |source: &str| source_derivation_path(source, None).unwrap();
+        assert_eq!(path("prompt://"), account_derivation_path(0));
+        assert_eq!(path("prompt://?key=1/0"), account_derivation_path(1));
+        assert_eq!(
+            path("prompt://?full-path=m/44'/501'/2'/0'"),
+            account_derivation_path(2)
+        );
+        assert_eq!(
+            source_derivation_path("prompt://", Some("m/44'/501'/3'/0'")).unwrap(),
+            account_derivation_path(3)
+        );
+
+        // 来源中的路径和 --derivation-path 不能同时指定，无法识别的参数返回错误
+        assert!(source_derivation_path("prompt://?key=1/0", Some("m/44'/501'/3'/0'")).is_err());
+        assert!(source_derivation_path("prompt://?account=1", None).is_err());
+    }
+
+    #[test]
+    fn prompt_sources_derive_distinct_keys() {
+        let mnemonic = generate_mnemonic(12).unwrap();
+        let derive = |source: &str| {
+            keypair_from_mnemonic(
+                &mnemonic,
+                "",
+                &source_derivation_path(source, None).unwrap(),
+            )
+            .unwrap()
+            .pubkey()
+        };
+        assert_ne!(derive("prompt://?key=0/0"), derive("prompt://?key=1/0"));
+        assert_eq!(derive("prompt://"), derive("prompt://?key=0/0"));
+    }
+}
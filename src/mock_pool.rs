@@ -0,0 +1,525 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    net::SocketAddr,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Response,
+    },
+    routing::{get, post},
+    Router,
+};
+use chrono::Utc;
+use colored::*;
+use ore_pool_api::state::pool_pda;
+use serde::{de::DeserializeOwned, Deserialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+};
+use tokio::sync::watch;
+use tokio_stream::{wrappers::WatchStream, Stream, StreamExt};
+
+use crate::{
+    args::MockPoolArgs,
+    pool::{
+        BalanceCommit, Challenge, ContributePayload, Member, MemberChallenge, PoolAddress,
+        RegisterPayload, UpdateBalancePayload,
+    },
+    pool_auth::{authenticate, request_nonce, signed_event, signed_json, ReplayGuard},
+    Miner,
+};
+
+// 难度等于最小难度时每个解决方案记入的余额
+const MOCK_REWARD_RATE: i64 = 1_000;
+
+/// 脚本中的一个挑战步骤
+#[derive(Deserialize, Clone, Debug)]
+struct ScriptedChallenge {
+    /// base58 编码的 32 字节挑战，缺省时随机生成
+    challenge: Option<String>,
+    min_difficulty: u64,
+    #[serde(default)]
+    buffer: u64,
+    /// 缺省时使用已注册成员数
+    num_total_members: Option<u64>,
+    /// 该挑战持续的秒数
+    duration: u64,
+}
+
+struct MockPoolState {
+    operator: Keypair,
+    pool_address: PoolAddress,
+    members: HashMap<Pubkey, Member>,
+    paid: HashMap<Pubkey, u64>,
+    /// 当前轮次中每个成员已记入的最高奖励，新挑战下发时清空
+    round_best: HashMap<Pubkey, i64>,
+    challenge: watch::Sender<MemberChallenge>,
+    replay: ReplayGuard,
+}
+
+type SharedState = Arc<Mutex<MockPoolState>>;
+
+impl Miner {
+    // 启动一个本地的模拟矿池，用于在没有真实矿池运营商的情况下测试矿池客户端和挖矿循环
+    pub async fn mock_pool(&self, args: MockPoolArgs) {
+        if let Err(err) = self.select_signer(&args.signer) {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
+        let script = match args.script {
+            Some(ref path) => {
+                let data = std::fs::read_to_string(path).expect("Failed to read challenge script");
+                serde_json::from_str::<Vec<ScriptedChallenge>>(&data)
+                    .expect("Failed to parse challenge script")
+            }
+            None => vec![ScriptedChallenge {
+                challenge: None,
+                min_difficulty: args.min_difficulty,
+                buffer: 0,
+                num_total_members: None,
+                duration: args.round_time,
+            }],
+        };
+        if script.is_empty() {
+            println!("Challenge script is empty");
+            return;
+        }
+
+        // 由当前签名者充当运营商，矿池地址由它派生
+        let state = new_state(self.local_signer(), &script[0]);
+
+        // 按脚本循环下发挑战
+        tokio::spawn(run_script(state.clone(), script));
+
+        let app = router(state);
+        let addr = SocketAddr::from(([127, 0, 0, 1], args.port));
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .expect("Failed to bind mock pool address");
+        println!("Mock pool listening on http://{}", addr);
+        axum::serve(listener, app)
+            .await
+            .expect("Mock pool server failed");
+    }
+}
+
+fn new_state(operator: Keypair, first: &ScriptedChallenge) -> SharedState {
+    let (address, bump) = pool_pda(operator.pubkey());
+    let (sender, _) = watch::channel(next_challenge(first, 0, 0));
+    Arc::new(Mutex::new(MockPoolState {
+        operator,
+        pool_address: PoolAddress { address, bump },
+        members: HashMap::new(),
+        paid: HashMap::new(),
+        round_best: HashMap::new(),
+        challenge: sender,
+        replay: ReplayGuard::default(),
+    }))
+}
+
+fn router(state: SharedState) -> Router {
+    Router::new()
+        .route("/register", post(register))
+        .route("/pool-address", get(pool_address))
+        .route("/challenge", get(challenge))
+        .route("/challenge/stream", get(challenge_stream))
+        .route("/contribute", post(contribute))
+        .route("/member/:authority", get(member))
+        .route("/update-balance", post(update_balance))
+        .with_state(state)
+}
+
+async fn run_script(state: SharedState, script: Vec<ScriptedChallenge>) {
+    let mut last_hash_at = 0;
+    for step in script.iter().cycle() {
+        let member_challenge = {
+            let mut state = state.lock().unwrap();
+            let challenge = next_challenge(step, state.members.len() as u64, last_hash_at);
+            state.round_best.clear();
+            state.challenge.send_replace(challenge);
+            challenge
+        };
+        last_hash_at = member_challenge.challenge.lash_hash_at;
+        println!(
+            "New challenge: {} (min difficulty {})",
+            bs58::encode(member_challenge.challenge.challenge).into_string(),
+            member_challenge.challenge.min_difficulty
+        );
+        tokio::time::sleep(Duration::from_secs(step.duration)).await;
+    }
+}
+
+fn next_challenge(
+    step: &ScriptedChallenge,
+    num_members: u64,
+    last_hash_at: i64,
+) -> MemberChallenge {
+    let challenge = match step.challenge {
+        Some(ref challenge) => {
+            let bytes = bs58::decode(challenge)
+                .into_vec()
+                .expect("Failed to decode scripted challenge");
+            bytes
+                .try_into()
+                .expect("Scripted challenge must be 32 bytes")
+        }
+        None => rand::random::<[u8; 32]>(),
+    };
+    MemberChallenge {
+        challenge: Challenge {
+            challenge,
+            // 保证每个挑战的时间戳都不同
+            lash_hash_at: Utc::now().timestamp().max(last_hash_at + 1),
+            min_difficulty: step.min_difficulty,
+            cutoff_time: step.duration,
+        },
+        buffer: step.buffer,
+        num_total_members: step.num_total_members.unwrap_or(num_members).max(1),
+    }
+}
+
+fn parse_body<T: DeserializeOwned>(body: &[u8]) -> Result<T, (StatusCode, String)> {
+    serde_json::from_slice(body).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))
+}
+
+async fn register(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, (StatusCode, String)> {
+    let payload = parse_body::<RegisterPayload>(&body)?;
+    let mut state = state.lock().unwrap();
+    authenticate(
+        &mut state.replay,
+        &headers,
+        "POST",
+        "/register",
+        &body,
+        &payload.authority,
+    )?;
+    if state.members.contains_key(&payload.authority) {
+        return Err((
+            StatusCode::CONFLICT,
+            "member already registered".to_string(),
+        ));
+    }
+    let member = Member {
+        id: state.members.len() as i64,
+        authority: payload.authority.to_string(),
+        pool_address: state.pool_address.address.to_string(),
+        total_balance: 0,
+        is_approved: true,
+        is_kyc: false,
+        is_synced: true,
+        nonce_range: None,
+    };
+    println!("Registered member {} ({})", member.id, member.authority);
+    state.members.insert(payload.authority, member.clone());
+    Ok(signed_json(&state.operator, &headers, &member))
+}
+
+async fn pool_address(State(state): State<SharedState>, headers: HeaderMap) -> Response {
+    let state = state.lock().unwrap();
+    signed_json(&state.operator, &headers, &state.pool_address)
+}
+
+async fn challenge(State(state): State<SharedState>, headers: HeaderMap) -> Response {
+    let state = state.lock().unwrap();
+    let challenge = *state.challenge.borrow();
+    signed_json(&state.operator, &headers, &challenge)
+}
+
+async fn challenge_stream(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let nonce = request_nonce(&headers);
+    let receiver = state.lock().unwrap().challenge.subscribe();
+    let stream = WatchStream::new(receiver).map(move |challenge| {
+        let state = state.lock().unwrap();
+        Ok(signed_event(
+            &state.operator,
+            nonce,
+            "challenge",
+            &challenge,
+        ))
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+async fn contribute(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let payload = parse_body::<ContributePayload>(&body)?;
+    let mut state = state.lock().unwrap();
+    authenticate(
+        &mut state.replay,
+        &headers,
+        "POST",
+        "/contribute",
+        &body,
+        &payload.authority,
+    )?;
+    let current = state.challenge.borrow().challenge;
+
+    // 校验解决方案
+    let solution = payload.solution();
+    if !drillx::is_valid_digest(&current.challenge, &solution.n, &solution.d) {
+        return Err((StatusCode::BAD_REQUEST, "invalid solution".to_string()));
+    }
+    let difficulty = solution.to_hash().difficulty() as u64;
+    if difficulty.lt(&current.min_difficulty) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "difficulty {} below min {}",
+                difficulty, current.min_difficulty
+            ),
+        ));
+    }
+
+    // 和真实矿池一样，每轮只按成员提交的最佳解决方案记入余额
+    if !state.members.contains_key(&payload.authority) {
+        return Err((StatusCode::NOT_FOUND, "member not registered".to_string()));
+    }
+    let reward = mock_reward(difficulty, current.min_difficulty);
+    let best = state.round_best.entry(payload.authority).or_insert(0);
+    let credit = reward.saturating_sub(*best).max(0);
+    *best = (*best).max(reward);
+    let member = state.members.get_mut(&payload.authority).unwrap();
+    member.total_balance = member.total_balance.saturating_add(credit);
+    println!(
+        "Contribution from member {}: difficulty {}, credited {}",
+        member.id, difficulty, credit
+    );
+    Ok(StatusCode::OK)
+}
+
+// 难度每高出最小难度一级，奖励翻倍
+fn mock_reward(difficulty: u64, min_difficulty: u64) -> i64 {
+    MOCK_REWARD_RATE.saturating_mul(2i64.saturating_pow((difficulty - min_difficulty) as u32))
+}
+
+async fn member(
+    State(state): State<SharedState>,
+    Path(authority): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let path = format!("/member/{}", authority);
+    let authority = Pubkey::from_str(&authority)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "invalid authority".to_string()))?;
+    let mut state = state.lock().unwrap();
+    authenticate(&mut state.replay, &headers, "GET", &path, &[], &authority)?;
+    let member = state
+        .members
+        .get(&authority)
+        .cloned()
+        .ok_or((StatusCode::NOT_FOUND, "member not registered".to_string()))?;
+    Ok(signed_json(&state.operator, &headers, &member))
+}
+
+// 模拟矿池没有链上账户，只记录已支付给成员的金额
+async fn update_balance(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, (StatusCode, String)> {
+    let payload = parse_body::<UpdateBalancePayload>(&body)?;
+    let mut state = state.lock().unwrap();
+    authenticate(
+        &mut state.replay,
+        &headers,
+        "POST",
+        "/update-balance",
+        &body,
+        &payload.authority,
+    )?;
+    let Some(member) = state.members.get(&payload.authority) else {
+        return Err((StatusCode::NOT_FOUND, "member not registered".to_string()));
+    };
+    let total_balance = member.total_balance.max(0) as u64;
+    let paid = state
+        .paid
+        .insert(payload.authority, total_balance)
+        .unwrap_or(0);
+    let commit = BalanceCommit {
+        total_balance,
+        paid: total_balance.saturating_sub(paid),
+        signature: Signature::default().to_string(),
+    };
+    Ok(signed_json(&state.operator, &headers, &commit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Error, pool::Pool};
+    use drillx::Solution;
+
+    fn step(min_difficulty: u64, duration: u64) -> ScriptedChallenge {
+        ScriptedChallenge {
+            challenge: None,
+            min_difficulty,
+            buffer: 2,
+            num_total_members: Some(4),
+            duration,
+        }
+    }
+
+    // 在随机端口上启动模拟矿池，返回连接它的客户端
+    async fn spawn_mock_pool(
+        operator: Keypair,
+        script: Vec<ScriptedChallenge>,
+    ) -> (Pool, SharedState) {
+        let state = new_state(operator, &script[0]);
+        tokio::spawn(run_script(state.clone(), script));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = router(state.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (Pool::new(format!("http://{}/", addr)), state)
+    }
+
+    #[tokio::test]
+    async fn pool_client_follows_mock_pool_challenges() {
+        let operator = Keypair::new();
+        let authority = operator.pubkey();
+        let (pool, _) = spawn_mock_pool(operator, vec![step(8, 1), step(9, 1)]).await;
+        pool.health_check().await.unwrap();
+        let pool_address = pool.get_pool_address().await.unwrap();
+        assert_eq!(pool_address.address, pool_pda(authority).0);
+
+        let first = pool.get_pool_challenge().await.unwrap();
+        assert_eq!(first.buffer, 2);
+        assert_eq!(first.num_total_members, 4);
+
+        // 脚本推进后客户端通过推送或轮询拿到新挑战
+        let next = pool
+            .get_updated_pool_challenge(first.challenge.lash_hash_at)
+            .await
+            .unwrap();
+        assert!(next.challenge.lash_hash_at > first.challenge.lash_hash_at);
+        assert_ne!(next.challenge.challenge, first.challenge.challenge);
+    }
+
+    // 在当前挑战上求出若干个不同难度的解决方案
+    fn solve(challenge: &[u8; 32], count: usize) -> Vec<(Solution, u64)> {
+        let mut solutions: Vec<(Solution, u64)> = vec![];
+        for nonce in 0u64.. {
+            for hash in drillx::hashes(challenge, &nonce.to_le_bytes()) {
+                let difficulty = hash.difficulty() as u64;
+                if solutions.iter().all(|(_, d)| d.ne(&difficulty)) {
+                    solutions.push((Solution::new(hash.d, nonce.to_le_bytes()), difficulty));
+                }
+            }
+            if solutions.len() >= count {
+                break;
+            }
+        }
+        solutions.sort_by_key(|(_, difficulty)| *difficulty);
+        solutions
+    }
+
+    #[tokio::test]
+    async fn pool_client_registers_with_mock_pool() {
+        let (pool, _) = spawn_mock_pool(Keypair::new(), vec![step(0, 60)]).await;
+        let member = Keypair::new();
+
+        // 注册前查询不到成员
+        let err = pool.get_pool_member(&member).await.unwrap_err();
+        assert!(matches!(err, Error::PoolStatus(404, _)));
+
+        let registered = pool.post_pool_register(&member).await.unwrap();
+        assert_eq!(registered.authority, member.pubkey().to_string());
+        assert_eq!(registered.total_balance, 0);
+        let fetched = pool.get_pool_member(&member).await.unwrap();
+        assert_eq!(fetched.id, registered.id);
+
+        // 重复注册被拒绝
+        let err = pool.post_pool_register(&member).await.unwrap_err();
+        assert!(matches!(err, Error::PoolStatus(409, _)));
+    }
+
+    #[tokio::test]
+    async fn pool_client_contributions_credit_best_solution_per_round() {
+        let (pool, state) = spawn_mock_pool(Keypair::new(), vec![step(0, 60)]).await;
+        let member = Keypair::new();
+        let challenge = pool.get_pool_challenge().await.unwrap().challenge;
+        let solutions = solve(&challenge.challenge, 2);
+        let (low, high) = (&solutions[0], &solutions[1]);
+
+        // 未注册的成员不能提交
+        let err = pool.post_pool_solution(&member, &low.0).await.unwrap_err();
+        assert!(matches!(err, Error::PoolStatus(404, _)));
+
+        pool.post_pool_register(&member).await.unwrap();
+        pool.post_pool_solution(&member, &low.0).await.unwrap();
+        let balance = pool.get_pool_member(&member).await.unwrap().total_balance;
+        assert_eq!(balance, mock_reward(low.1, 0));
+
+        // 同一轮更好的解决方案只补足差额，较差或重复的解决方案不再记入
+        pool.post_pool_solution(&member, &high.0).await.unwrap();
+        pool.post_pool_solution(&member, &low.0).await.unwrap();
+        pool.post_pool_solution(&member, &high.0).await.unwrap();
+        let balance = pool.get_pool_member(&member).await.unwrap().total_balance;
+        assert_eq!(balance, mock_reward(high.1, 0));
+
+        // 新一轮重新计算最佳解决方案
+        state.lock().unwrap().round_best.clear();
+        pool.post_pool_solution(&member, &low.0).await.unwrap();
+        let balance = pool.get_pool_member(&member).await.unwrap().total_balance;
+        assert_eq!(balance, mock_reward(high.1, 0) + mock_reward(low.1, 0));
+
+        // 无效的解决方案被拒绝
+        let invalid = Solution::new(high.0.d, (u64::from_le_bytes(high.0.n) + 1).to_le_bytes());
+        let err = pool
+            .post_pool_solution(&member, &invalid)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::PoolStatus(400, _)));
+    }
+
+    #[tokio::test]
+    async fn pool_client_updates_balance() {
+        let (pool, state) = spawn_mock_pool(Keypair::new(), vec![step(8, 60)]).await;
+        let member = Keypair::new();
+        pool.post_pool_register(&member).await.unwrap();
+
+        // 未记入余额时没有需要支付的金额
+        let commit = pool.post_update_pool_balance(&member).await.unwrap();
+        assert_eq!((commit.total_balance, commit.paid), (0, 0));
+
+        // 只支付上次之后新记入的余额
+        let credit = |amount: i64| {
+            let mut state = state.lock().unwrap();
+            let member = state.members.get_mut(&member.pubkey()).unwrap();
+            member.total_balance = member.total_balance.saturating_add(amount);
+        };
+        credit(5_000);
+        let commit = pool.post_update_pool_balance(&member).await.unwrap();
+        assert_eq!((commit.total_balance, commit.paid), (5_000, 5_000));
+        credit(1_000);
+        let commit = pool.post_update_pool_balance(&member).await.unwrap();
+        assert_eq!((commit.total_balance, commit.paid), (6_000, 1_000));
+
+        // 未注册的成员无法请求支付
+        let err = pool
+            .post_update_pool_balance(&Keypair::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::PoolStatus(404, _)));
+    }
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+/// 半开区间 [start, end) 表示的一段 nonce 空间
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NonceRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl NonceRange {
+    /// 完整的 nonce 空间
+    pub const FULL: Self = Self {
+        start: 0,
+        end: u64::MAX,
+    };
+
+    pub fn len(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len().eq(&0)
+    }
+
+    /// 将区间平均分为 count 份并返回第 index 份，余数依次分给前面的份额
+    ///
+    /// index 越界或 count 为 0 时返回 None。
+    pub fn partition(&self, index: u64, count: u64) -> Option<Self> {
+        if index.ge(&count) {
+            return None;
+        }
+        let len = self.len();
+        let size = len.checked_div(count)?;
+        let remainder = len.checked_rem(count)?;
+        let start = self
+            .start
+            .checked_add(index.checked_mul(size)?)?
+            .checked_add(index.min(remainder))?;
+        let end = start
+            .checked_add(size)?
+            .checked_add(u64::from(index.lt(&remainder)))?;
+        Some(Self { start, end })
+    }
+
+    /// 切分为 count 段互不重叠、首尾相接的子区间，例如每个核心一段
+    pub fn split(&self, count: u64) -> Vec<Self> {
+        (0..count)
+            .filter_map(|index| self.partition(index, count))
+            .collect()
+    }
+}
+
+/// 矿池成员的 nonce 空间：优先使用矿池分配的区间，否则按成员 id 在成员数中均分
+///
+/// 成员 id 超出挑战中报告的成员数时（例如刚注册的成员尚未计入）无法得到与其他成员不重叠的区间，返回 None。
+pub fn member_range(
+    assigned: Option<NonceRange>,
+    member_id: i64,
+    num_members: u64,
+) -> Option<NonceRange> {
+    if let Some(range) = assigned.filter(|range| !range.is_empty()) {
+        return Some(range);
+    }
+    let index = u64::try_from(member_id).ok()?;
+    NonceRange::FULL.partition(index, num_members)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partition_rejects_out_of_range_index_and_zero_count() {
+        assert_eq!(NonceRange::FULL.partition(3, 3), None);
+        assert_eq!(NonceRange::FULL.partition(0, 0), None);
+        assert_eq!(
+            NonceRange { start: 5, end: 5 }
+                .partition(0, 1)
+                .map(|r| r.len()),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn partition_spreads_remainder_and_ends_at_max() {
+        let range = NonceRange { start: 10, end: 20 };
+        assert_eq!(
+            range.partition(0, 3),
+            Some(NonceRange { start: 10, end: 14 })
+        );
+        assert_eq!(
+            range.partition(1, 3),
+            Some(NonceRange { start: 14, end: 17 })
+        );
+        assert_eq!(
+            range.partition(2, 3),
+            Some(NonceRange { start: 17, end: 20 })
+        );
+        assert_eq!(
+            NonceRange::FULL.partition(6, 7).map(|r| r.end),
+            Some(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn split_covers_range_without_overlap() {
+        assert!(NonceRange::FULL.split(0).is_empty());
+        let parts = NonceRange::FULL.split(5);
+        assert_eq!(parts.len(), 5);
+        assert_eq!(parts[0].start, 0);
+        assert_eq!(parts[4].end, u64::MAX);
+        for pair in parts.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start);
+        }
+    }
+
+    #[test]
+    fn member_range_never_overlaps_other_members() {
+        let assigned = NonceRange { start: 1, end: 2 };
+        assert_eq!(member_range(Some(assigned), 9, 2), Some(assigned));
+        assert_eq!(member_range(None, 2, 2), None);
+        assert_eq!(member_range(None, -1, 2), None);
+        assert_eq!(member_range(None, 0, 0), None);
+        assert_eq!(member_range(None, 1, 2), NonceRange::FULL.partition(1, 2));
+        assert_eq!(member_range(None, 1, 2).map(|r| r.end), Some(u64::MAX));
+    }
+}
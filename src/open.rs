@@ -1,29 +1,68 @@
+use std::str::FromStr;
+
+use colored::*;
+use solana_program::pubkey::Pubkey;
 use solana_sdk::signature::Signer;
 
-use crate::{send_and_confirm::ComputeBudget, utils::proof_pubkey, Miner};
+use crate::{
+    args::OpenArgs,
+    error::Error,
+    remote_signer::connect_remote_signer,
+    send_and_confirm::ComputeBudget,
+    utils::{get_proof_with_authority, proof_pubkey},
+    Miner,
+};
 
 impl Miner {
+    // 挖矿前确保证明已打开；证明已委托给其他矿工密钥时只提示，由调用方检查能否挖矿
     pub async fn open(&self) {
+        if let Err(err) = self.open_with_miner(self.authority()).await {
+            println!("{} {}", "WARNING".bold().yellow(), err);
+        }
+    }
+
+    // 打开证明账户，可以将挖矿权限委托给与 authority 不同的矿工密钥
+    pub async fn open_proof(&self, args: OpenArgs) -> Result<(), Error> {
+        self.select_signer(&args.signer)?;
+        if let Some(ref url) = args.remote.remote_signer {
+            connect_remote_signer(url).await?;
+        }
+        let miner = match args.miner {
+            Some(miner) => Pubkey::from_str(&miner)
+                .map_err(|_| Error::Internal("invalid miner address".to_string()))?,
+            None => self.authority(),
+        };
+        self.open_with_miner(miner).await
+    }
+
+    async fn open_with_miner(&self, miner: Pubkey) -> Result<(), Error> {
         // 如果矿工已经注册，则提前返回
-        let signer = self.signer(); // 获取矿工的签名者
-        let fee_payer = self.fee_payer(); // 获取交易的费用支付者
-        let proof_address = proof_pubkey(signer.pubkey()); // 从签名者的公钥派生出证明地址
+        let authority = self.authority(); // 获取证明的 authority（本地或远程签名者）
+        let fee_payer = self.local_fee_payer(); // 获取交易的费用支付者
+        let proof_address = proof_pubkey(authority); // 从签名者的公钥派生出证明地址
 
         // 检查证明地址的账户是否已经存在
         if self.rpc_client.get_account(&proof_address).await.is_ok() {
             println!("检查证明地址的账户是否已经存在");
-            return; // 如果存在，提前退出，因为矿工已经注册
+            let proof = get_proof_with_authority(&self.rpc_client, authority).await;
+            if proof.miner.ne(&miner) {
+                return Err(Error::Internal(format!(
+                    "proof is already open with miner {}. Use update-miner to change it.",
+                    proof.miner
+                )));
+            }
+            return Ok(()); // 如果存在，提前退出，因为矿工已经注册
         }
 
         // 如果尚未注册，继续生成挑战
         println!("正在生成挑战...");
 
         // 创建一个用于开启矿工的交易指令
-        let ix = luckycoin_api::sdk::open(signer.pubkey(), signer.pubkey(), fee_payer.pubkey());
+        let ix = luckycoin_api::sdk::open(authority, miner, fee_payer.pubkey());
 
         // 发送交易并确认
         self.send_and_confirm(&[ix], ComputeBudget::Fixed(400_000), false)
-            .await // 等待交易完成
-            .ok(); // 忽略发送过程中发生的任何错误
+            .await?; // 等待交易完成
+        Ok(())
     }
-}
\ No newline at end of file
+}
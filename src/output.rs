@@ -0,0 +1,64 @@
+use std::{io::Write, sync::OnceLock};
+
+use serde::Serialize;
+
+use crate::args::{OutputArgs, OutputFormat};
+
+// 进程级的输出格式，由顶层的 --output 在分派子命令前设置一次
+static OUTPUT_FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+/// 记录顶层 --output 选择的格式，之后的命令和诊断信息都按它输出
+pub fn configure_output(args: &OutputArgs) {
+    let _ = OUTPUT_FORMAT.set(args.output);
+}
+
+/// --output 选择了机器可读的格式时返回该格式，默认的文本输出返回 None
+pub fn machine_output() -> Option<OutputFormat> {
+    match OUTPUT_FORMAT.get().copied().unwrap_or(OutputFormat::Text) {
+        OutputFormat::Text => None,
+        format => Some(format),
+    }
+}
+
+/// 输出一组记录：JSON 和 YAML 为数组，CSV 为带表头的多行
+pub fn print_list<T: Serialize>(format: OutputFormat, records: &[T]) {
+    let result = match format {
+        OutputFormat::Text => Ok(()),
+        OutputFormat::Json => serde_json::to_string_pretty(records)
+            .map(|data| println!("{}", data))
+            .map_err(|err| err.to_string()),
+        OutputFormat::Yaml => serde_yaml::to_string(records)
+            .map(|data| print!("{}", data))
+            .map_err(|err| err.to_string()),
+        OutputFormat::Csv => write_csv(records),
+    };
+    if let Err(err) = result {
+        eprintln!("Failed to write {:?} output: {}", format, err);
+    }
+}
+
+/// 输出单条记录：JSON 和 YAML 为对象，CSV 为表头和一行
+pub fn print_record<T: Serialize>(format: OutputFormat, record: &T) {
+    let result = match format {
+        OutputFormat::Text => Ok(()),
+        OutputFormat::Json => serde_json::to_string_pretty(record)
+            .map(|data| println!("{}", data))
+            .map_err(|err| err.to_string()),
+        OutputFormat::Yaml => serde_yaml::to_string(record)
+            .map(|data| print!("{}", data))
+            .map_err(|err| err.to_string()),
+        OutputFormat::Csv => write_csv(std::slice::from_ref(record)),
+    };
+    if let Err(err) = result {
+        eprintln!("Failed to write {:?} output: {}", format, err);
+    }
+}
+
+fn write_csv<T: Serialize>(records: &[T]) -> Result<(), String> {
+    let mut writer = csv::Writer::from_writer(std::io::stdout());
+    for record in records.iter() {
+        writer.serialize(record).map_err(|err| err.to_string())?;
+    }
+    writer.flush().map_err(|err| err.to_string())?;
+    std::io::stdout().flush().map_err(|err| err.to_string())
+}
@@ -0,0 +1,479 @@
+use std::{
+    str::FromStr,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use colored::*;
+use drillx::Solution;
+use ore_api::consts::MINT_ADDRESS;
+use ore_pool_api::state::{member_pda, pool_pda, share_pda};
+use ore_utils::AccountDeserialize;
+use reqwest::{header::CONTENT_TYPE, Method, RequestBuilder, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use solana_client::{rpc_config::RpcProgramAccountsConfig, rpc_filter::RpcFilterType};
+use solana_rpc_client::spinner;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use url::Url;
+
+use crate::{
+    error::Error, nonce_range::NonceRange, pool_auth, pool_events::ChallengeSubscription, Miner,
+};
+
+const POOL_REQUEST_TIMEOUT: u64 = 10; // 单次请求超时（秒）
+const POOL_RETRIES: usize = 5; // 最大尝试次数
+const POOL_RETRY_DELAY: u64 = 500; // 首次重试延迟（毫秒），之后每次翻倍
+const POOL_CHALLENGE_DELAY: u64 = 1_000; // 轮询新挑战的间隔（毫秒）
+const POOL_PUSH_TIMEOUT: u64 = 5; // 等待推送的挑战多久后改为轮询一次（秒）
+const POOL_CHALLENGE_TIMEOUT: u64 = 180; // 多久没有新挑战视为矿池停止服务（秒）
+
+// 所有矿池请求共享同一个 HTTP 客户端
+static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// 矿池成员（链下记录）
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Member {
+    pub id: i64,
+    pub authority: String,
+    pub pool_address: String,
+    pub total_balance: i64,
+    pub is_approved: bool,
+    pub is_kyc: bool,
+    pub is_synced: bool,
+    /// 矿池为该成员分配的 nonce 区间，未分配时按成员 id 均分
+    #[serde(default)]
+    pub nonce_range: Option<NonceRange>,
+}
+
+/// 矿池在链上的地址
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct PoolAddress {
+    pub address: Pubkey,
+    pub bump: u8,
+}
+
+/// 矿池当前的挖矿挑战
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Challenge {
+    pub challenge: [u8; 32],
+    pub lash_hash_at: i64,
+    pub min_difficulty: u64,
+    pub cutoff_time: u64,
+}
+
+/// 下发给成员的挑战
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct MemberChallenge {
+    pub challenge: Challenge,
+    pub buffer: u64,
+    pub num_total_members: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct RegisterPayload {
+    pub authority: Pubkey,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct ContributePayload {
+    pub authority: Pubkey,
+    pub digest: [u8; 16],
+    pub nonce: [u8; 8],
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct UpdateBalancePayload {
+    pub authority: Pubkey,
+}
+
+/// 矿池运营商将成员链下余额提交上链的结果
+///
+/// 只在链下记账的矿池没有链上成员账户，运营商直接把尚未支付的余额转入成员的代币账户，
+/// 此时 total_balance 为累计支付的金额，paid 为本次支付的金额。
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BalanceCommit {
+    pub total_balance: u64,
+    #[serde(default)]
+    pub paid: u64,
+    pub signature: String,
+}
+
+impl ContributePayload {
+    pub fn solution(&self) -> Solution {
+        Solution::new(self.digest, self.nonce)
+    }
+}
+
+pub struct Pool {
+    pub http_client: reqwest::Client,
+    pub pool_url: String,
+    subscription: OnceLock<ChallengeSubscription>,
+    operator: OnceLock<Pubkey>,
+}
+
+impl Pool {
+    pub fn new(pool_url: String) -> Self {
+        let http_client = HTTP_CLIENT
+            .get_or_init(|| {
+                reqwest::Client::builder()
+                    .timeout(Duration::from_secs(POOL_REQUEST_TIMEOUT))
+                    .build()
+                    .unwrap_or_default()
+            })
+            .clone();
+        Pool {
+            http_client,
+            pool_url: pool_url.trim_end_matches('/').to_string(),
+            subscription: OnceLock::new(),
+            operator: OnceLock::new(),
+        }
+    }
+
+    /// 确定矿池运营商的公钥，之后的响应必须由该运营商签署
+    ///
+    /// 矿池地址由运营商派生，pinned 中能派生出该地址的公钥即为运营商；
+    /// 否则从链上矿池账户读取。只在链下记账的矿池（例如 pool-server）没有链上账户，必须通过 pinned 指定。
+    pub async fn load_pool_operator(
+        &self,
+        miner: &Miner,
+        pool_address: Pubkey,
+        pinned: &[String],
+    ) -> Result<Pubkey, Error> {
+        for operator in pinned.iter() {
+            let operator = Pubkey::from_str(operator)
+                .map_err(|_| Error::Internal(format!("Invalid pool operator: {}", operator)))?;
+            if pool_pda(operator).0.eq(&pool_address) {
+                return Ok(*self.operator.get_or_init(|| operator));
+            }
+        }
+        let data = miner
+            .rpc_client
+            .get_account_data(&pool_address)
+            .await
+            .map_err(|err| {
+                Error::Internal(format!(
+                    "no on-chain pool account at {} ({}); pass --pool-operator to pin the operator of an off-chain pool",
+                    pool_address, err
+                ))
+            })?;
+        let pool = ore_pool_api::state::Pool::try_from_bytes(&data)?;
+        Ok(*self.operator.get_or_init(|| pool.authority))
+    }
+
+    /// 确定矿池运营商；无法确定时返回错误，只有明确传入 insecure 才会继续并接受未签名的响应
+    pub async fn require_pool_operator(
+        &self,
+        miner: &Miner,
+        pool_address: Pubkey,
+        pinned: &[String],
+        insecure: bool,
+    ) -> Result<(), Error> {
+        match self.load_pool_operator(miner, pool_address, pinned).await {
+            Ok(_) => Ok(()),
+            Err(err) if insecure => {
+                println!(
+                    "{} Response signers will not be checked: {}",
+                    "WARNING".bold().yellow(),
+                    err
+                );
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// 注册为矿池成员，如果已经注册则返回已有的成员信息
+    pub async fn post_pool_register(&self, signer: &Keypair) -> Result<Member, Error> {
+        let url = format!("{}/register", self.pool_url);
+        let body = serde_json::to_vec(&RegisterPayload {
+            authority: signer.pubkey(),
+        })
+        .map_err(|err| Error::Internal(err.to_string()))?;
+        match self
+            .request(|nonce| self.signed(signer, Method::POST, &url, nonce, &body))
+            .await
+        {
+            Err(Error::PoolStatus(status, _)) if status == StatusCode::CONFLICT.as_u16() => {
+                self.get_pool_member(signer).await
+            }
+            res => res,
+        }
+    }
+
+    pub async fn get_pool_address(&self) -> Result<PoolAddress, Error> {
+        let url = format!("{}/pool-address", self.pool_url);
+        self.request(|nonce| self.unsigned(Method::GET, &url, nonce))
+            .await
+    }
+
+    pub async fn get_pool_member(&self, signer: &Keypair) -> Result<Member, Error> {
+        let url = format!("{}/member/{}", self.pool_url, signer.pubkey());
+        self.request(|nonce| self.signed(signer, Method::GET, &url, nonce, &[]))
+            .await
+    }
+
+    /// 从链上读取矿池成员账户
+    pub async fn get_pool_member_onchain(
+        &self,
+        miner: &Miner,
+        pool_address: Pubkey,
+    ) -> Result<ore_pool_api::state::Member, Error> {
+        let (member_address, _) = member_pda(miner.local_signer().pubkey(), pool_address);
+        let data = miner.rpc_client.get_account_data(&member_address).await?;
+        let member = ore_pool_api::state::Member::try_from_bytes(&data)?;
+        Ok(*member)
+    }
+
+    /// 从链上读取成员在矿池中的质押份额账户
+    pub async fn get_pool_share_onchain(
+        &self,
+        miner: &Miner,
+        pool_address: Pubkey,
+    ) -> Result<ore_pool_api::state::Share, Error> {
+        let (share_address, _) =
+            share_pda(miner.local_signer().pubkey(), pool_address, MINT_ADDRESS);
+        let data = miner.rpc_client.get_account_data(&share_address).await?;
+        let share = ore_pool_api::state::Share::try_from_bytes(&data)?;
+        Ok(*share)
+    }
+
+    /// 从链上读取矿池中所有成员的质押份额账户
+    pub async fn get_pool_shares_onchain(
+        &self,
+        miner: &Miner,
+        pool_address: Pubkey,
+    ) -> Result<Vec<ore_pool_api::state::Share>, Error> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::DataSize(
+                (8 + std::mem::size_of::<ore_pool_api::state::Share>()) as u64,
+            )]),
+            ..Default::default()
+        };
+        let accounts = miner
+            .rpc_client
+            .get_program_accounts_with_config(&ore_pool_api::ID, config)
+            .await?;
+        Ok(accounts
+            .iter()
+            .filter_map(|(_, account)| {
+                ore_pool_api::state::Share::try_from_bytes(&account.data).ok()
+            })
+            .filter(|share| share.pool.eq(&pool_address) && share.mint.eq(&MINT_ADDRESS))
+            .copied()
+            .collect())
+    }
+
+    /// 健康检查：矿池能够正常下发挑战
+    pub async fn health_check(&self) -> Result<(), Error> {
+        self.get_pool_challenge().await.map(|_| ())
+    }
+
+    pub async fn get_pool_challenge(&self) -> Result<MemberChallenge, Error> {
+        let url = format!("{}/challenge", self.pool_url);
+        self.request(|nonce| self.unsigned(Method::GET, &url, nonce))
+            .await
+    }
+
+    /// 等待挑战相对于 last_hash_at 发生变化
+    ///
+    /// 优先使用矿池推送的挑战；推送连接不可用或超时未收到推送时回退到轮询。
+    pub async fn get_updated_pool_challenge(
+        &self,
+        last_hash_at: i64,
+    ) -> Result<MemberChallenge, Error> {
+        let subscription = self.subscription();
+        let progress_bar = spinner::new_progress_bar();
+        let timer = Instant::now();
+        let mut retries = 0;
+        let mut pushed = false;
+        loop {
+            if timer.elapsed().as_secs().ge(&POOL_CHALLENGE_TIMEOUT) {
+                progress_bar.finish_with_message("No new challenge");
+                return Err(Error::Internal(format!(
+                    "pool has not served a new challenge in {} sec",
+                    POOL_CHALLENGE_TIMEOUT
+                )));
+            }
+            progress_bar.set_message(format!("Fetching new challenge... (retry {})", retries));
+            let challenge = match subscription.latest() {
+                Some(challenge) if pushed => challenge,
+                _ => self.get_pool_challenge().await?,
+            };
+            if challenge.challenge.lash_hash_at.ne(&last_hash_at) {
+                progress_bar.finish_with_message("Found new challenge");
+                return Ok(challenge);
+            }
+            retries += 1;
+            pushed = subscription
+                .wait_for_new(last_hash_at, Duration::from_secs(POOL_PUSH_TIMEOUT))
+                .await;
+            if !pushed && !subscription.is_connected() {
+                tokio::time::sleep(Duration::from_millis(POOL_CHALLENGE_DELAY)).await;
+            }
+        }
+    }
+
+    // 首次使用时启动挑战推送订阅
+    fn subscription(&self) -> &ChallengeSubscription {
+        self.subscription.get_or_init(|| {
+            ChallengeSubscription::start(
+                self.http_client.clone(),
+                &self.pool_url,
+                self.operator.get().copied(),
+            )
+        })
+    }
+
+    pub async fn post_pool_solution(
+        &self,
+        signer: &Keypair,
+        solution: &Solution,
+    ) -> Result<(), Error> {
+        let url = format!("{}/contribute", self.pool_url);
+        let body = serde_json::to_vec(&ContributePayload {
+            authority: signer.pubkey(),
+            digest: solution.d,
+            nonce: solution.n,
+        })
+        .map_err(|err| Error::Internal(err.to_string()))?;
+        self.send_with_retries(|nonce| self.signed(signer, Method::POST, &url, nonce, &body))
+            .await
+            .map(|_| ())
+    }
+
+    /// 请求矿池运营商将成员的链下余额提交上链
+    pub async fn post_update_pool_balance(&self, signer: &Keypair) -> Result<BalanceCommit, Error> {
+        let url = format!("{}/update-balance", self.pool_url);
+        let body = serde_json::to_vec(&UpdateBalancePayload {
+            authority: signer.pubkey(),
+        })
+        .map_err(|err| Error::Internal(err.to_string()))?;
+        self.request(|nonce| self.signed(signer, Method::POST, &url, nonce, &body))
+            .await
+    }
+
+    // 构建不需要成员签名的请求；随机数仍随请求发送，矿池用它为响应签名
+    fn unsigned(&self, method: Method, url: &str, nonce: u64) -> RequestBuilder {
+        self.http_client
+            .request(method, url)
+            .header(pool_auth::NONCE_HEADER, nonce.to_string())
+    }
+
+    // 构建由成员密钥签名的请求；每次构建都使用新的时间戳和随机数，重试不会被当作重放
+    fn signed(
+        &self,
+        signer: &Keypair,
+        method: Method,
+        url: &str,
+        nonce: u64,
+        body: &[u8],
+    ) -> RequestBuilder {
+        let path = Url::parse(url)
+            .map(|url| url.path().to_string())
+            .unwrap_or_default();
+        let headers = pool_auth::sign_request(signer, method.as_str(), &path, nonce, body);
+        let mut request = self.http_client.request(method, url);
+        if !body.is_empty() {
+            request = request
+                .header(CONTENT_TYPE, "application/json")
+                .body(body.to_vec());
+        }
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        request
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        build: impl Fn(u64) -> RequestBuilder,
+    ) -> Result<T, Error> {
+        let body = self.send_with_retries(build).await?;
+        serde_json::from_slice(&body).map_err(|err| Error::PoolDecode(err.to_string()))
+    }
+
+    // 发送请求，遇到网络错误、超时或服务端错误时按指数退避重试；每次尝试使用新的随机数
+    async fn send_with_retries(
+        &self,
+        build: impl Fn(u64) -> RequestBuilder,
+    ) -> Result<Vec<u8>, Error> {
+        let mut attempts = 0;
+        let mut delay = POOL_RETRY_DELAY;
+        loop {
+            attempts += 1;
+            let nonce = rand::random::<u64>();
+            match send(build(nonce), self.operator.get().copied(), nonce).await {
+                Err(err) if attempts < POOL_RETRIES && is_retryable(&err) => {
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                    delay = delay.saturating_mul(2);
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+/// 从 start 开始按顺序对矿池做健康检查，返回第一个可用矿池的下标；不可用的矿池打印警告后跳过
+pub async fn first_healthy(pools: &[Pool], start: usize) -> Option<usize> {
+    for i in (0..pools.len()).map(|n| (start + n) % pools.len()) {
+        match pools[i].health_check().await {
+            Ok(()) => return Some(i),
+            Err(err) => println!(
+                "{} Pool {} is unreachable: {}",
+                "WARNING".bold().yellow(),
+                pools[i].pool_url,
+                err
+            ),
+        }
+    }
+    None
+}
+
+/// 是否有矿池通过健康检查；不打印不可用矿池的警告，用于挖矿期间定期检查更优先的矿池
+pub async fn any_healthy(pools: &[Pool]) -> bool {
+    for pool in pools.iter() {
+        if pool.health_check().await.is_ok() {
+            return true;
+        }
+    }
+    false
+}
+
+// 发送请求并校验响应签名；已知矿池运营商时未签名的响应也会被拒绝
+async fn send(
+    request: RequestBuilder,
+    operator: Option<Pubkey>,
+    nonce: u64,
+) -> Result<Vec<u8>, Error> {
+    let resp = request.send().await?;
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        return Err(Error::PoolStatus(status.as_u16(), body));
+    }
+    let headers = resp.headers().clone();
+    let body = resp.bytes().await?.to_vec();
+    pool_auth::verify_response(
+        |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        },
+        &body,
+        operator,
+        nonce,
+    )
+    .map_err(Error::PoolSignature)?;
+    Ok(body)
+}
+
+fn is_retryable(err: &Error) -> bool {
+    match err {
+        Error::Reqwest(err) => err.is_timeout() || err.is_connect() || err.is_request(),
+        Error::PoolStatus(status, _) => {
+            *status >= 500 || *status == StatusCode::TOO_MANY_REQUESTS.as_u16()
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,311 @@
+use std::{collections::HashMap, str::FromStr};
+
+use axum::{
+    http::{header::CONTENT_TYPE, HeaderMap, HeaderValue, StatusCode},
+    response::{sse::Event, IntoResponse, Response},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+};
+
+pub const AUTHORITY_HEADER: &str = "x-ore-authority";
+pub const SIGNER_HEADER: &str = "x-ore-signer";
+pub const SIGNATURE_HEADER: &str = "x-ore-signature";
+pub const TIMESTAMP_HEADER: &str = "x-ore-timestamp";
+pub const NONCE_HEADER: &str = "x-ore-nonce";
+
+// 签名时间戳与本地时钟允许的最大偏差（秒）
+pub const MAX_CLOCK_SKEW: u64 = 60;
+
+/// 请求签名覆盖的规范化内容：方法、路径、时间戳、随机数和请求体
+pub fn canonical_request(
+    method: &str,
+    path: &str,
+    timestamp: i64,
+    nonce: u64,
+    body: &[u8],
+) -> Vec<u8> {
+    let mut message = format!("{}\n{}\n{}\n{}\n", method, path, timestamp, nonce).into_bytes();
+    message.extend_from_slice(body);
+    message
+}
+
+/// 响应签名覆盖的规范化内容：时间戳、请求的随机数和响应体
+///
+/// 包含请求的随机数使签名的响应只能作为这一次请求的应答，不能被重放给其他请求。
+pub fn canonical_response(timestamp: i64, nonce: u64, body: &[u8]) -> Vec<u8> {
+    let mut message = format!("{}\n{}\n", timestamp, nonce).into_bytes();
+    message.extend_from_slice(body);
+    message
+}
+
+/// 为矿池请求签名，返回需要附加的请求头
+pub fn sign_request(
+    signer: &Keypair,
+    method: &str,
+    path: &str,
+    nonce: u64,
+    body: &[u8],
+) -> Vec<(&'static str, String)> {
+    let timestamp = Utc::now().timestamp();
+    let signature = signer.sign_message(&canonical_request(method, path, timestamp, nonce, body));
+    vec![
+        (AUTHORITY_HEADER, signer.pubkey().to_string()),
+        (TIMESTAMP_HEADER, timestamp.to_string()),
+        (NONCE_HEADER, nonce.to_string()),
+        (SIGNATURE_HEADER, signature.to_string()),
+    ]
+}
+
+/// 校验请求签名，返回签名的成员公钥和随机数（供调用方防重放）
+pub fn verify_request(
+    header: impl Fn(&str) -> Option<String>,
+    method: &str,
+    path: &str,
+    body: &[u8],
+) -> Result<(Pubkey, u64), String> {
+    let authority = parse_header::<Pubkey>(&header, AUTHORITY_HEADER)?;
+    let timestamp = parse_header::<i64>(&header, TIMESTAMP_HEADER)?;
+    let nonce = parse_header::<u64>(&header, NONCE_HEADER)?;
+    let signature = parse_header::<Signature>(&header, SIGNATURE_HEADER)?;
+    if !within_clock_skew(timestamp) {
+        return Err("request timestamp is outside the allowed window".to_string());
+    }
+    let message = canonical_request(method, path, timestamp, nonce, body);
+    if !signature.verify(authority.as_ref(), &message) {
+        return Err("invalid request signature".to_string());
+    }
+    Ok((authority, nonce))
+}
+
+/// 记录近期使用过的请求随机数，用于拒绝重放的请求
+#[derive(Default)]
+pub struct ReplayGuard {
+    seen: HashMap<(Pubkey, u64), i64>,
+}
+
+impl ReplayGuard {
+    /// 随机数首次出现时返回 true
+    pub fn check(&mut self, authority: Pubkey, nonce: u64) -> bool {
+        let now = Utc::now().timestamp();
+        // 超出时间窗口的请求会被时间戳校验拒绝，不必继续保留其随机数
+        self.seen
+            .retain(|_, seen_at| now.abs_diff(*seen_at).le(&MAX_CLOCK_SKEW.saturating_mul(2)));
+        self.seen.insert((authority, nonce), now).is_none()
+    }
+}
+
+/// 矿池服务端校验请求由 authority 签名，且没有被重放
+pub fn authenticate(
+    replay: &mut ReplayGuard,
+    headers: &HeaderMap,
+    method: &str,
+    path: &str,
+    body: &[u8],
+    authority: &Pubkey,
+) -> Result<(), (StatusCode, String)> {
+    let (signer, nonce) = verify_request(
+        |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        },
+        method,
+        path,
+        body,
+    )
+    .map_err(|err| (StatusCode::UNAUTHORIZED, err))?;
+    if signer.ne(authority) {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            "request not signed by the member authority".to_string(),
+        ));
+    }
+    if !replay.check(signer, nonce) {
+        return Err((StatusCode::UNAUTHORIZED, "replayed request".to_string()));
+    }
+    Ok(())
+}
+
+/// 请求携带的随机数，服务端用它为响应签名；请求没有随机数时为 0
+pub fn request_nonce(headers: &HeaderMap) -> u64 {
+    headers
+        .get(NONCE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0)
+}
+
+/// 为矿池响应签名，返回需要附加的响应头；nonce 为所应答请求的随机数
+pub fn sign_response(signer: &Keypair, nonce: u64, body: &[u8]) -> Vec<(&'static str, String)> {
+    let timestamp = Utc::now().timestamp();
+    let signature = signer.sign_message(&canonical_response(timestamp, nonce, body));
+    vec![
+        (SIGNER_HEADER, signer.pubkey().to_string()),
+        (TIMESTAMP_HEADER, timestamp.to_string()),
+        (SIGNATURE_HEADER, signature.to_string()),
+    ]
+}
+
+/// 校验矿池对 nonce 请求的响应
+///
+/// 已知矿池运营商的公钥时响应必须由运营商签名；未知时只校验响应自带的签名，未签名的响应直接通过。
+pub fn verify_response(
+    header: impl Fn(&str) -> Option<String>,
+    body: &[u8],
+    operator: Option<Pubkey>,
+    nonce: u64,
+) -> Result<(), String> {
+    if header(SIGNATURE_HEADER).is_none() {
+        return match operator {
+            Some(operator) => Err(format!("response is not signed by {}", operator)),
+            None => Ok(()),
+        };
+    }
+    let signer = parse_header::<Pubkey>(&header, SIGNER_HEADER)?;
+    let timestamp = parse_header::<i64>(&header, TIMESTAMP_HEADER)?;
+    let signature = parse_header::<Signature>(&header, SIGNATURE_HEADER)?;
+    if let Some(operator) = operator {
+        if signer.ne(&operator) {
+            return Err(format!(
+                "response signed by {} instead of {}",
+                signer, operator
+            ));
+        }
+    }
+    if !within_clock_skew(timestamp) {
+        return Err("response timestamp is outside the allowed window".to_string());
+    }
+    if !signature.verify(signer.as_ref(), &canonical_response(timestamp, nonce, body)) {
+        return Err("invalid response signature".to_string());
+    }
+    Ok(())
+}
+
+/// 由运营商针对请求签名的 JSON 响应，成员可以据此校验响应确实来自矿池
+pub fn signed_json<T: Serialize>(signer: &Keypair, request: &HeaderMap, value: &T) -> Response {
+    let body = serde_json::to_vec(value).unwrap_or_default();
+    let headers = sign_response(signer, request_nonce(request), &body);
+    let mut response = ([(CONTENT_TYPE, "application/json")], body).into_response();
+    for (name, value) in headers {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            response.headers_mut().insert(name, value);
+        }
+    }
+    response
+}
+
+/// 由运营商针对订阅请求签名的推送事件
+pub fn signed_event<T: Serialize>(signer: &Keypair, nonce: u64, event: &str, value: &T) -> Event {
+    let data = serde_json::to_string(value).unwrap_or_default();
+    Event::default()
+        .event(event)
+        .json_data(SignedEvent::sign(signer, nonce, data))
+        .unwrap_or_default()
+}
+
+/// 推送的事件没有响应头，签名头随事件数据一起发送
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SignedEvent {
+    pub data: String,
+    pub headers: HashMap<String, String>,
+}
+
+impl SignedEvent {
+    /// 为订阅请求（随机数 nonce）推送的一条事件签名
+    pub fn sign(signer: &Keypair, nonce: u64, data: String) -> Self {
+        let headers = sign_response(signer, nonce, data.as_bytes())
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        Self { data, headers }
+    }
+
+    /// 按响应的规则校验事件，返回事件数据
+    pub fn verify(self, operator: Option<Pubkey>, nonce: u64) -> Result<String, String> {
+        verify_response(
+            |name| self.headers.get(name).cloned(),
+            self.data.as_bytes(),
+            operator,
+            nonce,
+        )?;
+        Ok(self.data)
+    }
+}
+
+fn within_clock_skew(timestamp: i64) -> bool {
+    Utc::now()
+        .timestamp()
+        .abs_diff(timestamp)
+        .le(&MAX_CLOCK_SKEW)
+}
+
+fn parse_header<T: FromStr>(
+    header: &impl Fn(&str) -> Option<String>,
+    name: &str,
+) -> Result<T, String> {
+    header(name)
+        .ok_or(format!("missing {} header", name))?
+        .parse::<T>()
+        .map_err(|_| format!("invalid {} header", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verify(
+        headers: &[(&'static str, String)],
+        body: &[u8],
+        operator: Option<Pubkey>,
+        nonce: u64,
+    ) -> Result<(), String> {
+        let headers: HashMap<&str, String> = headers.iter().cloned().collect();
+        verify_response(|name| headers.get(name).cloned(), body, operator, nonce)
+    }
+
+    #[test]
+    fn response_is_bound_to_operator_and_nonce() {
+        let operator = Keypair::new();
+        let headers = sign_response(&operator, 7, b"{}");
+        assert!(verify(&headers, b"{}", Some(operator.pubkey()), 7).is_ok());
+        assert!(verify(&headers, b"{}", Some(operator.pubkey()), 8).is_err());
+        assert!(verify(&headers, b"{ }", Some(operator.pubkey()), 7).is_err());
+        assert!(verify(&headers, b"{}", Some(Keypair::new().pubkey()), 7).is_err());
+    }
+
+    #[test]
+    fn unsigned_response_requires_unknown_operator() {
+        assert!(verify(&[], b"{}", None, 7).is_ok());
+        assert!(verify(&[], b"{}", Some(Keypair::new().pubkey()), 7).is_err());
+    }
+
+    #[test]
+    fn stale_response_is_rejected() {
+        let operator = Keypair::new();
+        let timestamp = Utc::now().timestamp() - MAX_CLOCK_SKEW as i64 - 1;
+        let signature = operator.sign_message(&canonical_response(timestamp, 7, b"{}"));
+        let headers = vec![
+            (SIGNER_HEADER, operator.pubkey().to_string()),
+            (TIMESTAMP_HEADER, timestamp.to_string()),
+            (SIGNATURE_HEADER, signature.to_string()),
+        ];
+        assert!(verify(&headers, b"{}", Some(operator.pubkey()), 7).is_err());
+    }
+
+    #[test]
+    fn signed_event_round_trips() {
+        let operator = Keypair::new();
+        let event = SignedEvent::sign(&operator, 7, "{}".to_string());
+        assert_eq!(
+            event.clone().verify(Some(operator.pubkey()), 7),
+            Ok("{}".to_string())
+        );
+        assert!(event.verify(Some(operator.pubkey()), 8).is_err());
+    }
+}
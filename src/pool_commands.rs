@@ -0,0 +1,343 @@
+use std::str::FromStr;
+
+use chrono::{Local, TimeZone};
+use colored::*;
+use ore_api::{consts::MINT_ADDRESS, state::Proof};
+use ore_pool_api::state::share_pda;
+use ore_utils::AccountDeserialize;
+use reqwest::StatusCode;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::{
+    args::{PoolArgs, PoolCommand, PoolSharesArgs, PoolStakeArgs, PoolStatusArgs, PoolUnstakeArgs},
+    cu_limits::CU_LIMIT_CLAIM,
+    error::Error,
+    mine::calculate_multiplier,
+    pool::Pool,
+    pool_history::load_pool_rounds,
+    send_and_confirm::ComputeBudget,
+    utils::{amount_f64_to_u64, amount_u64_to_string, get_config, proof_pubkey},
+    Miner,
+};
+
+impl Miner {
+    pub async fn pool(&self, args: PoolArgs) -> Result<(), Error> {
+        self.select_signer(&args.signer)?;
+        match args.command {
+            PoolCommand::Status(args) => self.pool_status(args).await,
+            PoolCommand::Stake(args) => self.pool_stake(args).await,
+            PoolCommand::Unstake(args) => self.pool_unstake(args).await,
+            PoolCommand::Shares(args) => self.pool_shares(args).await,
+        }
+    }
+
+    // 显示矿池成员信息、余额和最近几轮的记入情况，并标出与本地提交记录不一致的地方
+    async fn pool_status(&self, args: PoolStatusArgs) -> Result<(), Error> {
+        let pool = Pool::new(args.pool_url);
+        let pool_address = pool.get_pool_address().await?;
+        pool.require_pool_operator(
+            self,
+            pool_address.address,
+            args.pool_operator.as_slice(),
+            args.insecure_pool,
+        )
+        .await?;
+        // 只读取成员信息，不会替未注册的签名者注册
+        let member = match pool.get_pool_member(&self.local_signer()).await {
+            Ok(member) => member,
+            Err(Error::PoolStatus(status, _)) if status == StatusCode::NOT_FOUND.as_u16() => {
+                println!("{}: {}", "Pool".bold(), pool.pool_url);
+                println!(
+                    "{} is not a member of this pool.",
+                    self.local_signer().pubkey()
+                );
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+        let onchain = pool
+            .get_pool_member_onchain(self, pool_address.address)
+            .await
+            .ok();
+
+        // 成员信息和余额
+        let offchain_total = member.total_balance.max(0) as u64;
+        println!("{}: {}", "Pool".bold(), pool.pool_url);
+        println!("{}: {}", "Pool address".bold(), pool_address.address);
+        println!("{}: {}", "Member id".bold(), member.id);
+        println!("{}: {}", "Approved".bold(), member.is_approved);
+        println!(
+            "{}: {} ORE",
+            "Off-chain total".bold(),
+            amount_u64_to_string(offchain_total)
+        );
+        match onchain {
+            Some(onchain) => {
+                println!(
+                    "{}: {} ORE",
+                    "On-chain total".bold(),
+                    amount_u64_to_string(onchain.total_balance)
+                );
+                println!(
+                    "{}: {} ORE",
+                    "Claimable".bold(),
+                    amount_u64_to_string(onchain.balance)
+                );
+            }
+            None => println!("{}: not found", "On-chain member".bold()),
+        }
+
+        // 最近几轮的提交和记入情况
+        let rounds = load_pool_rounds(&pool.pool_url, &member.authority);
+        let credits: Vec<Option<i64>> = rounds
+            .iter()
+            .enumerate()
+            .map(|(i, round)| {
+                i.checked_sub(1).map(|prev| {
+                    round
+                        .total_balance
+                        .saturating_sub(rounds[prev].total_balance)
+                })
+            })
+            .collect();
+        println!("\n{}", "Recent rounds".bold());
+        println!(
+            "  {:<20} {:>10} {:>10} {:>16}",
+            "Submitted at", "Difficulty", "Accepted", "Credit (ORE)"
+        );
+        let start = rounds.len().saturating_sub(args.rounds);
+        for (round, credit) in rounds.iter().zip(credits.iter()).skip(start) {
+            println!(
+                "  {:<20} {:>10} {:>10} {:>16}",
+                Local
+                    .timestamp_opt(round.timestamp, 0)
+                    .single()
+                    .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_default(),
+                round.difficulty,
+                round.accepted,
+                credit
+                    .map(|credit| format_credit(credit))
+                    .unwrap_or("-".to_string())
+            );
+        }
+
+        // 核对：提交被接受但随后没有记入、余额减少、链上与链下不一致
+        let mut discrepancies = vec![];
+        for i in start..rounds.len() {
+            let next_credit = credits.get(i + 1).copied().flatten();
+            if rounds[i].accepted
+                && next_credit.is_some()
+                && credits[i].unwrap_or(0).le(&0)
+                && next_credit.unwrap_or(0).le(&0)
+            {
+                discrepancies.push(format!(
+                    "Accepted solution (difficulty {}) was never credited",
+                    rounds[i].difficulty
+                ));
+            }
+            if credits[i].unwrap_or(0).lt(&0) {
+                discrepancies.push(format!(
+                    "Pool balance decreased by {} ORE",
+                    format_credit(-credits[i].unwrap_or(0))
+                ));
+            }
+        }
+        if let Some(last) = rounds.last() {
+            if member.total_balance.lt(&last.total_balance) {
+                discrepancies.push(format!(
+                    "Off-chain total is {} ORE lower than last recorded",
+                    format_credit(last.total_balance.saturating_sub(member.total_balance))
+                ));
+            }
+        }
+        if let Some(onchain) = onchain {
+            if onchain.total_balance.gt(&offchain_total) {
+                discrepancies.push("On-chain total exceeds the pool's off-chain total".to_string());
+            } else if onchain.total_balance.lt(&offchain_total) {
+                println!(
+                    "\n{} ORE is not yet committed on-chain. Run update-pool-balance to commit it.",
+                    amount_u64_to_string(offchain_total.saturating_sub(onchain.total_balance))
+                );
+            }
+        }
+        for discrepancy in discrepancies.iter() {
+            println!("{} {}", "WARNING".bold().yellow(), discrepancy);
+        }
+        Ok(())
+    }
+
+    // 将 ORE 质押到矿池的份额账户，份额账户不存在时先创建
+    async fn pool_stake(&self, args: PoolStakeArgs) -> Result<(), Error> {
+        let signer = self.local_signer();
+        let pool = Pool::new(args.pool_url);
+        let pool_address = pool.get_pool_address().await?;
+
+        // 确定发送者的代币账户和质押金额
+        let sender = match args.token_account {
+            Some(address) => Pubkey::from_str(&address)
+                .map_err(|_| Error::Internal("invalid token account address".to_string()))?,
+            None => get_associated_token_address(&signer.pubkey(), &MINT_ADDRESS),
+        };
+        let Ok(Some(token_account)) = self.rpc_client.get_token_account(&sender).await else {
+            return Err(Error::Internal("failed to fetch token account".to_string()));
+        };
+        let amount = match args.amount {
+            Some(amount) => amount_f64_to_u64(amount),
+            None => u64::from_str(&token_account.token_amount.amount)
+                .map_err(|_| Error::Internal("failed to parse token balance".to_string()))?,
+        };
+
+        // 构建指令集
+        let mut ixs = vec![];
+        let (share_address, _) = share_pda(signer.pubkey(), pool_address.address, MINT_ADDRESS);
+        if self.rpc_client.get_account(&share_address).await.is_err() {
+            ixs.push(ore_pool_api::sdk::open_share(
+                signer.pubkey(),
+                MINT_ADDRESS,
+                pool_address.address,
+            ));
+        }
+        ixs.push(ore_pool_api::sdk::stake(
+            signer.pubkey(),
+            MINT_ADDRESS,
+            pool_address.address,
+            sender,
+            amount,
+        ));
+        self.send_and_confirm(&ixs, ComputeBudget::Fixed(CU_LIMIT_CLAIM), false)
+            .await?;
+        println!(
+            "Staked {} ORE into pool {}",
+            amount_u64_to_string(amount),
+            pool_address.address
+        );
+        Ok(())
+    }
+
+    // 从矿池的份额账户中取回质押的 ORE
+    async fn pool_unstake(&self, args: PoolUnstakeArgs) -> Result<(), Error> {
+        let signer = self.local_signer();
+        let pool = Pool::new(args.pool_url);
+        let pool_address = pool.get_pool_address().await?;
+        let share = pool
+            .get_pool_share_onchain(self, pool_address.address)
+            .await?;
+        let amount = args.amount.map(amount_f64_to_u64).unwrap_or(share.balance);
+        if amount.gt(&share.balance) {
+            return Err(Error::Internal(format!(
+                "cannot unstake {} ORE, only {} ORE is staked",
+                amount_u64_to_string(amount),
+                amount_u64_to_string(share.balance)
+            )));
+        }
+
+        // 如果需要，创建接收者的代币账户
+        let mut ixs = vec![];
+        let wallet = match args.to {
+            Some(to) => Pubkey::from_str(&to)
+                .map_err(|_| Error::Internal("invalid wallet address".to_string()))?,
+            None => signer.pubkey(),
+        };
+        let recipient = get_associated_token_address(&wallet, &MINT_ADDRESS);
+        if self.rpc_client.get_token_account(&recipient).await.is_err() {
+            ixs.push(
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &signer.pubkey(),
+                    &wallet,
+                    &MINT_ADDRESS,
+                    &spl_token::id(),
+                ),
+            );
+        }
+        ixs.push(ore_pool_api::sdk::unstake(
+            signer.pubkey(),
+            MINT_ADDRESS,
+            pool_address.address,
+            recipient,
+            amount,
+        ));
+        self.send_and_confirm(&ixs, ComputeBudget::Fixed(CU_LIMIT_CLAIM), false)
+            .await?;
+        println!(
+            "Unstaked {} ORE from pool {}",
+            amount_u64_to_string(amount),
+            pool_address.address
+        );
+        Ok(())
+    }
+
+    // 列出矿池中每个成员的质押份额，以及各自在矿池质押倍数中所占的部分
+    async fn pool_shares(&self, args: PoolSharesArgs) -> Result<(), Error> {
+        let pool = Pool::new(args.pool_url);
+        let pool_address = pool.get_pool_address().await?;
+
+        // 矿池的质押倍数由矿池证明中的余额决定，只在链下记账的矿池没有链上证明
+        let config = get_config(&self.rpc_client).await;
+        let data = self
+            .rpc_client
+            .get_account_data(&proof_pubkey(pool_address.address))
+            .await
+            .map_err(|err| {
+                Error::Internal(format!(
+                    "pool {} has no on-chain proof ({})",
+                    pool_address.address, err
+                ))
+            })?;
+        let proof = Proof::try_from_bytes(&data)?;
+        let multiplier = calculate_multiplier(proof.balance, config.top_balance);
+
+        // 每个成员的份额按矿池的总质押计算，证明余额中还包括未领取的挖矿奖励
+        let mut shares = pool
+            .get_pool_shares_onchain(self, pool_address.address)
+            .await?;
+        shares.sort_by(|a, b| b.balance.cmp(&a.balance));
+        let total_stake: u64 = shares.iter().map(|share| share.balance).sum();
+        println!("{}: {}", "Pool".bold(), pool.pool_url);
+        println!("{}: {}", "Pool address".bold(), pool_address.address);
+        println!(
+            "{}: {} ORE",
+            "Total staked".bold(),
+            amount_u64_to_string(total_stake)
+        );
+        println!("{}: {:.4}x", "Pool multiplier".bold(), multiplier);
+        println!(
+            "\n  {:<44} {:>16} {:>10} {:>10}",
+            "Member", "Staked (ORE)", "Share", "Boost"
+        );
+        let signer = self.local_signer().pubkey();
+        for share in shares.iter() {
+            let part = if total_stake.gt(&0) {
+                share.balance as f64 / total_stake as f64
+            } else {
+                0.0
+            };
+            println!(
+                "{} {:<44} {:>16} {:>9.4}% {:>9.4}x",
+                if share.authority.eq(&signer) {
+                    "*"
+                } else {
+                    " "
+                },
+                share.authority.to_string(),
+                amount_u64_to_string(share.balance),
+                part * 100.0,
+                (multiplier - 1.0) * part
+            );
+        }
+        if shares.is_empty() {
+            println!("  No stake in this pool.");
+        }
+        Ok(())
+    }
+}
+
+fn format_credit(credit: i64) -> String {
+    if credit.lt(&0) {
+        format!("-{}", amount_u64_to_string(credit.unsigned_abs()))
+    } else {
+        amount_u64_to_string(credit as u64)
+    }
+}
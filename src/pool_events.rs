@@ -0,0 +1,258 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use colored::*;
+use reqwest::header::ACCEPT;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::watch;
+
+use crate::{
+    error::Error,
+    pool::MemberChallenge,
+    pool_auth::{SignedEvent, NONCE_HEADER},
+};
+
+const POOL_STREAM_IDLE_TIMEOUT: u64 = 60; // 连接无数据（包括心跳）多久后重连（秒）
+const POOL_STREAM_TIMEOUT: u64 = 60 * 60; // 单个连接的最长时间（秒）
+const POOL_RECONNECT_DELAY: u64 = 500; // 首次重连延迟（毫秒），之后每次翻倍
+const POOL_RECONNECT_MAX_DELAY: u64 = 30_000; // 最大重连延迟（毫秒）
+
+/// 通过 server-sent events 订阅矿池推送的挑战
+///
+/// 后台任务负责连接和自动重连；连接断开期间 `connected` 为 false，调用方应回退到轮询。
+/// 已知矿池运营商时，推送的挑战必须由运营商针对本次连接的随机数签名。
+pub struct ChallengeSubscription {
+    receiver: watch::Receiver<Option<MemberChallenge>>,
+    connected: Arc<AtomicBool>,
+}
+
+impl ChallengeSubscription {
+    pub fn start(http_client: reqwest::Client, pool_url: &str, operator: Option<Pubkey>) -> Self {
+        let (sender, receiver) = watch::channel(None);
+        let connected = Arc::new(AtomicBool::new(false));
+        let url = format!("{}/challenge/stream", pool_url);
+        tokio::spawn(subscribe(
+            http_client,
+            url,
+            operator,
+            sender,
+            connected.clone(),
+        ));
+        Self {
+            receiver,
+            connected,
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// 最近一次推送的挑战，仅在连接正常时返回
+    pub fn latest(&self) -> Option<MemberChallenge> {
+        if !self.is_connected() {
+            return None;
+        }
+        *self.receiver.borrow()
+    }
+
+    /// 等待与 last_hash_at 不同的挑战被推送，超时或连接断开时返回 false
+    pub async fn wait_for_new(&self, last_hash_at: i64, timeout: Duration) -> bool {
+        if !self.is_connected() {
+            return false;
+        }
+        let mut receiver = self.receiver.clone();
+        let is_new = |challenge: &Option<MemberChallenge>| {
+            challenge.map_or(false, |c| c.challenge.lash_hash_at.ne(&last_hash_at))
+        };
+        if is_new(&receiver.borrow_and_update()) {
+            return true;
+        }
+        loop {
+            match tokio::time::timeout(timeout, receiver.changed()).await {
+                Ok(Ok(())) => {
+                    if is_new(&receiver.borrow_and_update()) {
+                        return true;
+                    }
+                }
+                _ => return false,
+            }
+        }
+    }
+}
+
+// 保持订阅连接，断开后按指数退避重连，直到所有接收端被释放
+async fn subscribe(
+    http_client: reqwest::Client,
+    url: String,
+    operator: Option<Pubkey>,
+    sender: watch::Sender<Option<MemberChallenge>>,
+    connected: Arc<AtomicBool>,
+) {
+    let mut delay = POOL_RECONNECT_DELAY;
+    loop {
+        if let Err(err) = stream_challenges(
+            &http_client,
+            &url,
+            operator,
+            &sender,
+            &connected,
+            &mut delay,
+        )
+        .await
+        {
+            // 签名无效的推送不会被采用，连接断开后由调用方回退到轮询
+            if let Error::PoolSignature(_) = err {
+                println!("Rejected pool challenge stream: {}", err);
+            }
+        }
+        connected.store(false, Ordering::Relaxed);
+        if sender.is_closed() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        delay = delay.saturating_mul(2).min(POOL_RECONNECT_MAX_DELAY);
+    }
+}
+
+async fn stream_challenges(
+    http_client: &reqwest::Client,
+    url: &str,
+    operator: Option<Pubkey>,
+    sender: &watch::Sender<Option<MemberChallenge>>,
+    connected: &AtomicBool,
+    delay: &mut u64,
+) -> Result<(), Error> {
+    let nonce = rand::random::<u64>();
+    let mut resp = http_client
+        .get(url)
+        .header(ACCEPT, "text/event-stream")
+        .header(NONCE_HEADER, nonce.to_string())
+        .timeout(Duration::from_secs(POOL_STREAM_TIMEOUT))
+        .send()
+        .await?;
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        return Err(Error::PoolStatus(status.as_u16(), body));
+    }
+    connected.store(true, Ordering::Relaxed);
+    *delay = POOL_RECONNECT_DELAY;
+
+    // 先按字节缓冲到事件边界（空行），再整体解码，避免多字节字符被分块截断；
+    // 无法解析的事件打印警告后跳过，签名无效时断开连接
+    let mut buffer: Vec<u8> = vec![];
+    loop {
+        let chunk =
+            tokio::time::timeout(Duration::from_secs(POOL_STREAM_IDLE_TIMEOUT), resp.chunk())
+                .await
+                .map_err(|_| Error::Internal("pool challenge stream idle".to_string()))??;
+        let Some(chunk) = chunk else {
+            return Ok(());
+        };
+        buffer.extend_from_slice(&chunk);
+        while let Some(event) = next_event(&mut buffer) {
+            let parsed = String::from_utf8(event)
+                .map_err(|err| Error::PoolDecode(err.to_string()))
+                .and_then(|event| parse_event(&event, operator, nonce));
+            match parsed {
+                Ok(Some(challenge)) => {
+                    sender.send_replace(Some(challenge));
+                }
+                Ok(None) => {}
+                Err(Error::PoolDecode(err)) => {
+                    println!(
+                        "{} Skipping malformed pool event: {}",
+                        "WARNING".bold().yellow(),
+                        err
+                    );
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+// 从缓冲区取出第一个完整的事件（不含结尾的空行），事件之间以 \n\n 或 \r\n\r\n 分隔
+fn next_event(buffer: &mut Vec<u8>) -> Option<Vec<u8>> {
+    let find = |boundary: &[u8]| {
+        buffer
+            .windows(boundary.len())
+            .position(|window| window.eq(boundary))
+            .map(|end| (end, boundary.len()))
+    };
+    let (end, len) = [find(b"\n\n"), find(b"\r\n\r\n")]
+        .into_iter()
+        .flatten()
+        .min_by_key(|(end, _)| *end)?;
+    let event = buffer[..end].to_vec();
+    buffer.drain(..end + len);
+    Some(event)
+}
+
+// 事件数据是带签名的 SignedEvent，其中的 data 为挑战的 JSON
+fn parse_event(
+    event: &str,
+    operator: Option<Pubkey>,
+    nonce: u64,
+) -> Result<Option<MemberChallenge>, Error> {
+    let mut event_type = "challenge";
+    let mut data = vec![];
+    for line in event.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            event_type = value.trim();
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.trim_start());
+        }
+    }
+    if data.is_empty() || event_type.ne("challenge") {
+        return Ok(None);
+    }
+    let event = serde_json::from_str::<SignedEvent>(&data.join("\n"))
+        .map_err(|err| Error::PoolDecode(err.to_string()))?;
+    let data = event
+        .verify(operator, nonce)
+        .map_err(Error::PoolSignature)?;
+    serde_json::from_str(&data)
+        .map(Some)
+        .map_err(|err| Error::PoolDecode(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_event_waits_for_the_boundary() {
+        // 多字节字符被拆到两个分块中，补齐之前不会切出事件
+        let data = "event: challenge\ndata: {\"note\":\"挑战\"}\n\n".as_bytes();
+        let split = data.iter().position(|byte| byte.ge(&0x80)).unwrap() + 1;
+        let mut buffer = data[..split].to_vec();
+        assert!(next_event(&mut buffer).is_none());
+        buffer.extend_from_slice(&data[split..]);
+        let event = String::from_utf8(next_event(&mut buffer).unwrap()).unwrap();
+        assert_eq!(event, "event: challenge\ndata: {\"note\":\"挑战\"}");
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn next_event_splits_crlf_events() {
+        let mut buffer = b"data: a\r\n\r\ndata: b\n\ndata: c".to_vec();
+        assert_eq!(next_event(&mut buffer).unwrap(), b"data: a".to_vec());
+        assert_eq!(next_event(&mut buffer).unwrap(), b"data: b".to_vec());
+        assert!(next_event(&mut buffer).is_none());
+        assert_eq!(buffer, b"data: c".to_vec());
+    }
+
+    #[test]
+    fn parse_event_reports_malformed_data() {
+        let err = parse_event("data: not json", None, 1).unwrap_err();
+        assert!(matches!(err, Error::PoolDecode(_)));
+        assert!(parse_event(": keep-alive", None, 1).unwrap().is_none());
+    }
+}
@@ -0,0 +1,70 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::config_path;
+
+const POOL_HISTORY_FILE: &str = "pool-rounds.jsonl";
+
+/// 一轮矿池挖矿的本地记录：提交的难度，以及提交后矿池记入的链下总余额
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PoolRound {
+    pub timestamp: i64,
+    pub pool_url: String,
+    pub authority: String,
+    pub lash_hash_at: i64,
+    pub difficulty: u32,
+    pub accepted: bool,
+    pub total_balance: i64,
+}
+
+impl PoolRound {
+    pub fn new(
+        pool_url: &str,
+        authority: &str,
+        lash_hash_at: i64,
+        difficulty: u32,
+        accepted: bool,
+        total_balance: i64,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now().timestamp(),
+            pool_url: pool_url.to_string(),
+            authority: authority.to_string(),
+            lash_hash_at,
+            difficulty,
+            accepted,
+            total_balance,
+        }
+    }
+}
+
+/// 追加一轮记录，写入失败只打印提示，不影响挖矿
+pub fn record_pool_round(round: &PoolRound) {
+    let path = config_path(POOL_HISTORY_FILE);
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| {
+            let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+            writeln!(file, "{}", serde_json::to_string(round)?)
+        });
+    if let Err(err) = result {
+        println!("Failed to record pool round to {:?}: {}", path, err);
+    }
+}
+
+/// 读取指定矿池和成员的全部记录，按时间顺序排列
+pub fn load_pool_rounds(pool_url: &str, authority: &str) -> Vec<PoolRound> {
+    let Ok(data) = fs::read_to_string(config_path(POOL_HISTORY_FILE)) else {
+        return vec![];
+    };
+    data.lines()
+        .filter_map(|line| serde_json::from_str::<PoolRound>(line).ok())
+        .filter(|round| round.pool_url.eq(pool_url) && round.authority.eq(authority))
+        .collect()
+}
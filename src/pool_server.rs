@@ -0,0 +1,831 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    fs,
+    net::SocketAddr,
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Response,
+    },
+    routing::{get, post},
+    Router,
+};
+use colored::*;
+use drillx::Solution;
+use ore_api::consts::MINT_ADDRESS;
+use ore_pool_api::state::pool_pda;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use solana_program::instruction::Instruction;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+};
+use spl_associated_token_account::get_associated_token_address;
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio_stream::{wrappers::WatchStream, Stream, StreamExt};
+
+use crate::{
+    args::PoolServerArgs,
+    cu_limits::CU_LIMIT_CLAIM,
+    nonce_range::NonceRange,
+    pool::{
+        BalanceCommit, Challenge, ContributePayload, Member, MemberChallenge, PoolAddress,
+        RegisterPayload, UpdateBalancePayload,
+    },
+    pool_auth::{authenticate, request_nonce, signed_event, signed_json, ReplayGuard},
+    send_and_confirm::ComputeBudget,
+    utils::{
+        amount_u64_to_string, config_path, get_clock, get_config, get_proof_with_authority,
+        get_updated_proof_with_authority, proof_pubkey,
+    },
+    Miner,
+};
+
+const POOL_SERVER_STATE_FILE: &str = "pool-server.json";
+const POOL_SOLUTION_DELAY: u64 = 1_000; // 截止后仍未收到合格解决方案时的等待间隔（毫秒）
+const POOL_SOLUTION_TIMEOUT: u64 = 30; // 截止后最多再等待多久的解决方案，超时后重新开始本轮（秒）
+const MAX_WEIGHT_SHIFT: u32 = 64; // 难度权重的最大指数，保证奖励乘以权重不会溢出 u128
+
+/// 持久化到磁盘的矿池成员和余额
+#[derive(Serialize, Deserialize, Default)]
+struct PoolLedger {
+    members: Vec<Member>,
+    /// 已经从运营商证明支付给每个成员的累计金额，按成员公钥索引
+    #[serde(default)]
+    paid: HashMap<String, u64>,
+}
+
+/// 成员请求支付记入的余额，由运营商在挖矿循环之外依次处理
+struct PayoutRequest {
+    authority: Pubkey,
+    reply: oneshot::Sender<Result<BalanceCommit, String>>,
+}
+
+/// 成员在本轮提交的最佳解决方案
+#[derive(Clone, Copy)]
+struct Contribution {
+    digest: [u8; 16],
+    nonce: [u8; 8],
+    difficulty: u32,
+}
+
+struct PoolServerState {
+    path: PathBuf,
+    operator: Keypair,
+    pool_address: PoolAddress,
+    members: HashMap<Pubkey, Member>,
+    challenge: watch::Sender<MemberChallenge>,
+    contributions: HashMap<Pubkey, Contribution>,
+    paid: HashMap<Pubkey, u64>,
+    payouts: mpsc::UnboundedSender<PayoutRequest>,
+    replay: ReplayGuard,
+}
+
+type SharedState = Arc<Mutex<PoolServerState>>;
+
+impl PoolServerState {
+    // 开始新一轮：下发新挑战，清空上一轮的提交，并按成员数重新分配 nonce 区间
+    fn start_round(&mut self, challenge: Challenge, buffer: u64) {
+        let count = self
+            .members
+            .values()
+            .map(|member| member.id.max(0) as u64 + 1)
+            .max()
+            .unwrap_or(1);
+        for member in self.members.values_mut() {
+            member.nonce_range = NonceRange::FULL.partition(member.id.max(0) as u64, count);
+        }
+        self.contributions.clear();
+        self.challenge.send_replace(MemberChallenge {
+            challenge,
+            buffer,
+            num_total_members: count,
+        });
+        self.save();
+    }
+
+    // 本轮难度最高的解决方案
+    fn best_contribution(&self) -> Option<Contribution> {
+        self.contributions
+            .values()
+            .max_by_key(|contribution| contribution.difficulty)
+            .copied()
+    }
+
+    // 按本轮各成员解决方案的难度权重分配奖励
+    //
+    // 权重为 2^(difficulty - 本轮最低难度)，指数不超过 64，因此 reward * weight 不会超过 u128。
+    fn credit(&mut self, reward: u64) {
+        let lowest = self
+            .contributions
+            .values()
+            .map(|contribution| contribution.difficulty)
+            .min()
+            .unwrap_or(0);
+        let weights: Vec<(Pubkey, u128)> = self
+            .contributions
+            .iter()
+            .map(|(authority, contribution)| {
+                let shift = contribution
+                    .difficulty
+                    .saturating_sub(lowest)
+                    .min(MAX_WEIGHT_SHIFT);
+                (*authority, 1u128 << shift)
+            })
+            .collect();
+        let total_weight = weights
+            .iter()
+            .fold(0u128, |total, (_, weight)| total.saturating_add(*weight));
+        if total_weight.eq(&0) {
+            return;
+        }
+        for (authority, weight) in weights {
+            let share = (reward as u128)
+                .saturating_mul(weight)
+                .checked_div(total_weight)
+                .unwrap_or(0);
+            if let Some(member) = self.members.get_mut(&authority) {
+                member.total_balance = member
+                    .total_balance
+                    .saturating_add(i64::try_from(share).unwrap_or(i64::MAX));
+                println!(
+                    "Credited member {} ({}): {} ORE",
+                    member.id,
+                    member.authority,
+                    amount_u64_to_string(share as u64)
+                );
+            }
+        }
+        self.save();
+    }
+
+    // 成员已支付的累计金额和尚未支付的记入余额
+    fn unpaid(&self, authority: &Pubkey) -> Option<(u64, u64)> {
+        let member = self.members.get(authority)?;
+        let paid = self.paid.get(authority).copied().unwrap_or(0);
+        Some((
+            paid,
+            (member.total_balance.max(0) as u64).saturating_sub(paid),
+        ))
+    }
+
+    fn record_payout(&mut self, authority: Pubkey, amount: u64) {
+        let paid = self.paid.entry(authority).or_insert(0);
+        *paid = paid.saturating_add(amount);
+        self.save();
+    }
+
+    // 写入失败只打印提示，不影响矿池运行
+    fn save(&self) {
+        let mut members: Vec<Member> = self.members.values().cloned().collect();
+        members.sort_by_key(|member| member.id);
+        let paid = self
+            .paid
+            .iter()
+            .map(|(authority, amount)| (authority.to_string(), *amount))
+            .collect();
+        let result = self
+            .path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                let data = serde_json::to_string_pretty(&PoolLedger { members, paid })?;
+                fs::write(&self.path, data)
+            });
+        if let Err(err) = result {
+            println!("Failed to persist pool state to {:?}: {}", self.path, err);
+        }
+    }
+}
+
+impl Miner {
+    // 运行一个矿池运营商服务：成员挖掘运营商证明的挑战，矿池每轮提交最佳解决方案并按难度记入成员余额
+    pub async fn pool_server(&self, args: PoolServerArgs) {
+        if let Err(err) = self.select_signer(&args.signer) {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
+        // 如果需要，打开运营商的证明账户
+        let signer = self.local_signer();
+        self.open().await;
+
+        // 读取已有的成员和余额
+        let path = args
+            .state_file
+            .clone()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| config_path(POOL_SERVER_STATE_FILE));
+        let ledger: PoolLedger = fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        let (address, bump) = pool_pda(signer.pubkey());
+        let (sender, _) = watch::channel(MemberChallenge {
+            challenge: Challenge {
+                challenge: [0; 32],
+                lash_hash_at: 0,
+                min_difficulty: 0,
+                cutoff_time: 0,
+            },
+            buffer: args.buffer_time,
+            num_total_members: 1,
+        });
+        let (payouts, payout_requests) = mpsc::unbounded_channel();
+        let state = Arc::new(Mutex::new(PoolServerState {
+            path,
+            operator: signer,
+            pool_address: PoolAddress { address, bump },
+            members: ledger
+                .members
+                .into_iter()
+                .filter_map(|member| {
+                    Pubkey::from_str(&member.authority)
+                        .ok()
+                        .map(|authority| (authority, member))
+                })
+                .collect(),
+            challenge: sender,
+            contributions: HashMap::new(),
+            paid: ledger
+                .paid
+                .into_iter()
+                .filter_map(|(authority, amount)| {
+                    Pubkey::from_str(&authority)
+                        .ok()
+                        .map(|authority| (authority, amount))
+                })
+                .collect(),
+            payouts,
+            replay: ReplayGuard::default(),
+        }));
+
+        let app = Router::new()
+            .route("/register", post(register))
+            .route("/pool-address", get(pool_address))
+            .route("/challenge", get(challenge))
+            .route("/challenge/stream", get(challenge_stream))
+            .route("/contribute", post(contribute))
+            .route("/member/:authority", get(member))
+            .route("/update-balance", post(update_balance))
+            .with_state(state.clone());
+        let addr = SocketAddr::from_str(&format!("{}:{}", args.host, args.port))
+            .expect("Failed to parse pool server address");
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .expect("Failed to bind pool server address");
+        println!("Pool server listening on http://{}", addr);
+
+        // 链下矿池没有链上矿池账户，成员需要用 --pool-operator 指定运营商才能校验响应
+        let operator = state.lock().unwrap().operator.pubkey();
+        println!(
+            "Pool operator: {} (members pass --pool-operator {})",
+            operator, operator
+        );
+
+        // 同时运行 HTTP 服务、挖矿轮次和成员支付；提交解决方案和支付都会改变证明余额，不能同时进行
+        let proof_lock = tokio::sync::Mutex::new(());
+        tokio::select! {
+            res = axum::serve(listener, app) => {
+                if let Err(err) = res {
+                    println!("Pool server failed: {}", err);
+                }
+            }
+            _ = self.run_pool_rounds(state.clone(), args.buffer_time, &proof_lock) => {}
+            _ = self.run_payouts(state, payout_requests, &proof_lock) => {}
+        }
+    }
+
+    async fn run_pool_rounds(
+        &self,
+        state: SharedState,
+        buffer_time: u64,
+        proof_lock: &tokio::sync::Mutex<()>,
+    ) {
+        let signer = self.local_signer();
+        let mut last_hash_at = 0;
+        let mut restarted_at: Option<i64> = None;
+        loop {
+            // 等待运营商证明的新挑战并下发给成员；重新开始的轮次挑战不变，直接读取当前证明
+            let config = get_config(&self.rpc_client).await;
+            let proof = match restarted_at {
+                Some(_) => get_proof_with_authority(&self.rpc_client, signer.pubkey()).await,
+                None => {
+                    get_updated_proof_with_authority(
+                        &self.rpc_client,
+                        signer.pubkey(),
+                        last_hash_at,
+                    )
+                    .await
+                }
+            };
+            last_hash_at = proof.last_hash_at;
+
+            // 重新开始的轮次从重新开始的时刻计时，并以该时刻作为挑战时间，成员据此识别出新一轮
+            let round_start = restarted_at.take().unwrap_or(0).max(proof.last_hash_at);
+            let cutoff_time = self.get_cutoff(round_start, buffer_time).await;
+            state.lock().unwrap().start_round(
+                Challenge {
+                    challenge: proof.challenge,
+                    lash_hash_at: round_start,
+                    min_difficulty: config.min_difficulty,
+                    cutoff_time,
+                },
+                buffer_time,
+            );
+            println!(
+                "\n\nNew challenge: {} (min difficulty {}, cutoff {} sec)",
+                bs58::encode(proof.challenge).into_string(),
+                config.min_difficulty,
+                cutoff_time
+            );
+
+            // 收集成员提交的解决方案，截止后再等待一段时间，仍没有合格的解决方案则重新开始本轮
+            tokio::time::sleep(Duration::from_secs(cutoff_time)).await;
+            let deadline = Instant::now() + Duration::from_secs(POOL_SOLUTION_TIMEOUT);
+            let best = loop {
+                if let Some(best) = state.lock().unwrap().best_contribution() {
+                    break Some(best);
+                }
+                if Instant::now().ge(&deadline) {
+                    break None;
+                }
+                tokio::time::sleep(Duration::from_millis(POOL_SOLUTION_DELAY)).await;
+            };
+            let Some(best) = best else {
+                println!(
+                    "{} No solutions received from {} members. Restarting the round...",
+                    "WARNING".bold().yellow(),
+                    state.lock().unwrap().members.len()
+                );
+                restarted_at = Some(get_clock(&self.rpc_client).await.unix_timestamp);
+                continue;
+            };
+            println!(
+                "Submitting best solution (difficulty {}) from {} contributors",
+                best.difficulty,
+                state.lock().unwrap().contributions.len()
+            );
+
+            // 构建指令集
+            let mut ixs = vec![ore_api::instruction::auth(proof_pubkey(signer.pubkey()))];
+            let mut compute_budget = 500_000;
+            if self.should_reset(config).await && rand::thread_rng().gen_range(0..100).eq(&0) {
+                compute_budget += 100_000;
+                ixs.push(ore_api::instruction::reset(signer.pubkey()));
+            }
+            ixs.push(ore_api::instruction::mine(
+                signer.pubkey(),
+                signer.pubkey(),
+                self.find_bus().await,
+                Solution::new(best.digest, best.nonce),
+            ));
+
+            // 提交交易，成功后按证明余额的变化记入成员余额
+            let guard = proof_lock.lock().await;
+            let before = get_proof_with_authority(&self.rpc_client, signer.pubkey()).await;
+            if let Err(err) = self.send_and_check(&ixs, compute_budget).await {
+                println!(
+                    "{} Failed to submit pool solution: {}",
+                    "ERROR".bold().red(),
+                    err
+                );
+                restarted_at = Some(get_clock(&self.rpc_client).await.unix_timestamp);
+                continue;
+            }
+            let after = get_proof_with_authority(&self.rpc_client, signer.pubkey()).await;
+            drop(guard);
+            let reward = after.balance.saturating_sub(before.balance);
+            println!("Pool reward: {} ORE", amount_u64_to_string(reward));
+            state.lock().unwrap().credit(reward);
+        }
+    }
+
+    // 发送交易；返回错误时检查已发送的交易是否已经上链，避免丢弃已生效的提交或重复支付
+    async fn send_and_check(
+        &self,
+        ixs: &[Instruction],
+        compute_budget: u32,
+    ) -> Result<Signature, String> {
+        let mut sent = vec![];
+        let err = match self
+            .send_and_confirm_tracked(
+                ixs,
+                ComputeBudget::Fixed(compute_budget),
+                false,
+                None,
+                &[],
+                &mut sent,
+            )
+            .await
+        {
+            Ok(signature) => return Ok(signature),
+            Err(err) => err,
+        };
+        match self.find_landed(&sent).await {
+            Ok(Some(signature)) => {
+                println!(
+                    "{} Transaction landed despite error ({}): {}",
+                    "WARNING".bold().yellow(),
+                    err,
+                    signature
+                );
+                Ok(signature)
+            }
+            Ok(None) => Err(err.to_string()),
+            Err(status_err) => Err(format!(
+                "{} (failed to check transaction status: {})",
+                err, status_err
+            )),
+        }
+    }
+
+    // 依次处理成员的支付请求，同一成员的并发请求不会重复支付
+    async fn run_payouts(
+        &self,
+        state: SharedState,
+        mut requests: mpsc::UnboundedReceiver<PayoutRequest>,
+        proof_lock: &tokio::sync::Mutex<()>,
+    ) {
+        while let Some(request) = requests.recv().await {
+            let result = self.pay_member(&state, request.authority, proof_lock).await;
+            request.reply.send(result).ok();
+        }
+    }
+
+    // 从运营商证明中领取成员尚未支付的记入余额，转入成员的代币账户
+    async fn pay_member(
+        &self,
+        state: &SharedState,
+        authority: Pubkey,
+        proof_lock: &tokio::sync::Mutex<()>,
+    ) -> Result<BalanceCommit, String> {
+        let Some((paid, amount)) = state.lock().unwrap().unpaid(&authority) else {
+            return Err("member not registered".to_string());
+        };
+        if amount.eq(&0) {
+            return Ok(BalanceCommit {
+                total_balance: paid,
+                paid: 0,
+                signature: String::new(),
+            });
+        }
+
+        // 如果需要，由运营商为成员创建代币账户
+        let signer = self.local_signer();
+        let beneficiary = get_associated_token_address(&authority, &MINT_ADDRESS);
+        let mut ixs = vec![];
+        if !matches!(
+            self.rpc_client.get_token_account(&beneficiary).await,
+            Ok(Some(_))
+        ) {
+            ixs.push(
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &signer.pubkey(),
+                    &authority,
+                    &MINT_ADDRESS,
+                    &spl_token::id(),
+                ),
+            );
+        }
+        ixs.push(ore_api::instruction::claim(
+            signer.pubkey(),
+            beneficiary,
+            amount,
+        ));
+        let _guard = proof_lock.lock().await;
+        let signature = self.send_and_check(&ixs, CU_LIMIT_CLAIM).await?;
+        state.lock().unwrap().record_payout(authority, amount);
+        println!(
+            "Paid {} ORE to {}: {}",
+            amount_u64_to_string(amount),
+            authority,
+            signature
+        );
+        Ok(BalanceCommit {
+            total_balance: paid.saturating_add(amount),
+            paid: amount,
+            signature: signature.to_string(),
+        })
+    }
+}
+
+async fn register(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, (StatusCode, String)> {
+    let payload = serde_json::from_slice::<RegisterPayload>(&body)
+        .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    let mut state = state.lock().unwrap();
+    authenticate(
+        &mut state.replay,
+        &headers,
+        "POST",
+        "/register",
+        &body,
+        &payload.authority,
+    )?;
+    if state.members.contains_key(&payload.authority) {
+        return Err((
+            StatusCode::CONFLICT,
+            "member already registered".to_string(),
+        ));
+    }
+    let id = state
+        .members
+        .values()
+        .map(|member| member.id.saturating_add(1))
+        .max()
+        .unwrap_or(0);
+    let member = Member {
+        id,
+        authority: payload.authority.to_string(),
+        pool_address: state.pool_address.address.to_string(),
+        total_balance: 0,
+        is_approved: true,
+        is_kyc: false,
+        is_synced: true,
+        // 本轮的区间已经分配给其他成员，新成员在下一轮开始时才取得区间
+        nonce_range: None,
+    };
+    println!("Registered member {} ({})", member.id, member.authority);
+    state.members.insert(payload.authority, member.clone());
+    state.save();
+    Ok(signed_json(&state.operator, &headers, &member))
+}
+
+async fn pool_address(State(state): State<SharedState>, headers: HeaderMap) -> Response {
+    let state = state.lock().unwrap();
+    signed_json(&state.operator, &headers, &state.pool_address)
+}
+
+async fn challenge(State(state): State<SharedState>, headers: HeaderMap) -> Response {
+    let state = state.lock().unwrap();
+    let challenge = *state.challenge.borrow();
+    signed_json(&state.operator, &headers, &challenge)
+}
+
+async fn challenge_stream(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let nonce = request_nonce(&headers);
+    let receiver = state.lock().unwrap().challenge.subscribe();
+    let stream = WatchStream::new(receiver).map(move |challenge| {
+        let state = state.lock().unwrap();
+        Ok(signed_event(
+            &state.operator,
+            nonce,
+            "challenge",
+            &challenge,
+        ))
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+async fn contribute(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let payload = serde_json::from_slice::<ContributePayload>(&body)
+        .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    let mut state = state.lock().unwrap();
+    authenticate(
+        &mut state.replay,
+        &headers,
+        "POST",
+        "/contribute",
+        &body,
+        &payload.authority,
+    )?;
+    let Some(member) = state.members.get(&payload.authority) else {
+        return Err((StatusCode::NOT_FOUND, "member not registered".to_string()));
+    };
+
+    // 解决方案必须来自成员分配到的 nonce 区间
+    let Some(range) = member.nonce_range else {
+        return Err((
+            StatusCode::CONFLICT,
+            "no nonce range assigned until the next round".to_string(),
+        ));
+    };
+    let nonce = u64::from_le_bytes(payload.nonce);
+    if nonce.lt(&range.start) || nonce.ge(&range.end) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "nonce outside the assigned range".to_string(),
+        ));
+    }
+
+    // 校验解决方案
+    let current = state.challenge.borrow().challenge;
+    let solution = payload.solution();
+    if !drillx::is_valid_digest(&current.challenge, &solution.n, &solution.d) {
+        return Err((StatusCode::BAD_REQUEST, "invalid solution".to_string()));
+    }
+    let difficulty = solution.to_hash().difficulty();
+    if (difficulty as u64).lt(&current.min_difficulty) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "difficulty {} below min {}",
+                difficulty, current.min_difficulty
+            ),
+        ));
+    }
+
+    // 只保留每个成员本轮的最佳解决方案
+    let contribution = Contribution {
+        digest: payload.digest,
+        nonce: payload.nonce,
+        difficulty,
+    };
+    let improved = state
+        .contributions
+        .get(&payload.authority)
+        .map_or(true, |best| difficulty.gt(&best.difficulty));
+    if improved {
+        state.contributions.insert(payload.authority, contribution);
+    }
+    Ok(StatusCode::OK)
+}
+
+async fn member(
+    State(state): State<SharedState>,
+    Path(authority): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let path = format!("/member/{}", authority);
+    let authority = Pubkey::from_str(&authority)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "invalid authority".to_string()))?;
+    let mut state = state.lock().unwrap();
+    authenticate(&mut state.replay, &headers, "GET", &path, &[], &authority)?;
+    let member = state
+        .members
+        .get(&authority)
+        .cloned()
+        .ok_or((StatusCode::NOT_FOUND, "member not registered".to_string()))?;
+    Ok(signed_json(&state.operator, &headers, &member))
+}
+
+// 链下矿池没有链上成员账户可以提交，直接把成员尚未支付的记入余额转入成员的代币账户
+async fn update_balance(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, (StatusCode, String)> {
+    let payload = serde_json::from_slice::<UpdateBalancePayload>(&body)
+        .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    let (reply, receiver) = oneshot::channel();
+    {
+        let mut state = state.lock().unwrap();
+        authenticate(
+            &mut state.replay,
+            &headers,
+            "POST",
+            "/update-balance",
+            &body,
+            &payload.authority,
+        )?;
+        if !state.members.contains_key(&payload.authority) {
+            return Err((StatusCode::NOT_FOUND, "member not registered".to_string()));
+        }
+        state
+            .payouts
+            .send(PayoutRequest {
+                authority: payload.authority,
+                reply,
+            })
+            .map_err(|_| {
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "payouts are not running".to_string(),
+                )
+            })?;
+    }
+    let commit = receiver
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "payouts are not running".to_string(),
+            )
+        })?
+        .map_err(|err| (StatusCode::BAD_GATEWAY, format!("payout failed: {}", err)))?;
+    let state = state.lock().unwrap();
+    Ok(signed_json(&state.operator, &headers, &commit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool_state(contributions: &[u32]) -> PoolServerState {
+        let (challenge, _) = watch::channel(MemberChallenge {
+            challenge: Challenge {
+                challenge: [0; 32],
+                lash_hash_at: 0,
+                min_difficulty: 0,
+                cutoff_time: 0,
+            },
+            buffer: 0,
+            num_total_members: 1,
+        });
+        let mut state = PoolServerState {
+            path: std::env::temp_dir().join(format!("pool-server-{}.json", rand::random::<u64>())),
+            operator: Keypair::new(),
+            pool_address: PoolAddress {
+                address: Pubkey::new_unique(),
+                bump: 0,
+            },
+            members: HashMap::new(),
+            challenge,
+            contributions: HashMap::new(),
+            paid: HashMap::new(),
+            payouts: mpsc::unbounded_channel().0,
+            replay: ReplayGuard::default(),
+        };
+        for (id, difficulty) in contributions.iter().enumerate() {
+            let authority = Pubkey::new_unique();
+            state.members.insert(
+                authority,
+                Member {
+                    id: id as i64,
+                    authority: authority.to_string(),
+                    pool_address: state.pool_address.address.to_string(),
+                    total_balance: 0,
+                    is_approved: true,
+                    is_kyc: false,
+                    is_synced: true,
+                    nonce_range: None,
+                },
+            );
+            state.contributions.insert(
+                authority,
+                Contribution {
+                    digest: [0; 16],
+                    nonce: [0; 8],
+                    difficulty: *difficulty,
+                },
+            );
+        }
+        state
+    }
+
+    fn balances(state: &PoolServerState) -> Vec<i64> {
+        let mut members: Vec<&Member> = state.members.values().collect();
+        members.sort_by_key(|member| member.id);
+        members.iter().map(|member| member.total_balance).collect()
+    }
+
+    #[test]
+    fn credit_weights_by_relative_difficulty() {
+        let mut state = pool_state(&[40, 41, 42]);
+        state.credit(7_000);
+        assert_eq!(balances(&state), vec![1_000, 2_000, 4_000]);
+        fs::remove_file(&state.path).ok();
+    }
+
+    #[test]
+    fn credit_does_not_saturate_large_rewards() {
+        let reward = u64::MAX / 2;
+        let mut state = pool_state(&[100, 100]);
+        state.credit(reward);
+        assert_eq!(balances(&state), vec![(reward / 2) as i64; 2]);
+        fs::remove_file(&state.path).ok();
+
+        // 难度相差超过 64 时权重封顶，奖励仍按比例分完而不是因溢出被截断
+        let mut state = pool_state(&[0, 200]);
+        state.credit(reward);
+        let credited: u64 = balances(&state).iter().map(|balance| *balance as u64).sum();
+        assert!(credited.le(&reward) && credited.ge(&(reward - 2)));
+        fs::remove_file(&state.path).ok();
+    }
+
+    #[test]
+    fn payouts_cover_only_unpaid_credits() {
+        let mut state = pool_state(&[40]);
+        let authority = *state.members.keys().next().unwrap();
+        state.credit(5_000);
+        assert_eq!(state.unpaid(&authority), Some((0, 5_000)));
+        state.record_payout(authority, 5_000);
+        assert_eq!(state.unpaid(&authority), Some((5_000, 0)));
+        state.credit(1_000);
+        assert_eq!(state.unpaid(&authority), Some((5_000, 1_000)));
+        assert_eq!(state.unpaid(&Pubkey::new_unique()), None);
+        fs::remove_file(&state.path).ok();
+    }
+}
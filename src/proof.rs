@@ -1,21 +1,42 @@
 use std::str::FromStr;
 
+use colored::*;
 use ore_api::consts::TOKEN_DECIMALS;
+use serde::Serialize;
 use solana_program::pubkey::Pubkey;
 use solana_sdk::signature::Signer;
 use spl_token::amount_to_ui_amount;
 
 use crate::{
     args::ProofArgs,
+    output::{machine_output, print_record},
     utils::{get_proof, proof_pubkey},
     Miner,
 };
 
+/// proof 的机器可读输出
+#[derive(Serialize)]
+struct ProofRecord {
+    address: String,
+    authority: String,
+    balance: f64,
+    last_hash: String,
+    last_hash_at: i64,
+    last_stake_at: i64,
+    miner: String,
+    total_hashes: u64,
+    total_rewards: f64,
+}
+
 impl Miner {
     // 用于获取并显示矿工的证明信息
     pub async fn proof(&self, args: ProofArgs) {
+        if let Err(err) = self.select_signer(&args.signer) {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
         // 获取签名者(矿工的公钥)
-        let signer = self.signer();
+        let signer = self.local_signer();
         // 根据提供的地址参数决定使用哪个地址
         let address = if let Some(address) = args.address {
             // 如果提供了地址，则将其转换为Pubkey格式
@@ -26,6 +47,21 @@ impl Miner {
         };
         // 从 RPC 客户端获取指定地址的证明信息
         let proof = get_proof(&self.rpc_client, address).await;
+        if let Some(format) = machine_output() {
+            let record = ProofRecord {
+                address: address.to_string(),
+                authority: proof.authority.to_string(),
+                balance: amount_to_ui_amount(proof.balance, TOKEN_DECIMALS),
+                last_hash: solana_sdk::hash::Hash::new_from_array(proof.last_hash).to_string(),
+                last_hash_at: proof.last_hash_at,
+                last_stake_at: proof.last_stake_at,
+                miner: proof.miner.to_string(),
+                total_hashes: proof.total_hashes,
+                total_rewards: amount_to_ui_amount(proof.total_rewards, TOKEN_DECIMALS),
+            };
+            print_record(format, &record);
+            return;
+        }
         // 打印地址信息
         println!("Address: {:?}", address);
         // 打印授权信息
@@ -0,0 +1,169 @@
+use std::{str::FromStr, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    signer::presigner::Presigner,
+    transaction::Transaction,
+};
+use tokio::sync::OnceCell;
+
+use crate::{error::Error, Miner};
+
+// 远程签名服务的访问令牌
+pub const SIGNER_TOKEN_ENV: &str = "LUCKYCOIN_SIGNER_TOKEN";
+
+const SIGNER_REQUEST_TIMEOUT: u64 = 10; // 签名请求超时（秒）
+
+// 配置后，authority 的签名都交给远程签名服务完成
+static REMOTE_SIGNER: OnceCell<RemoteSigner> = OnceCell::const_new();
+
+#[derive(Serialize, Deserialize)]
+pub struct PubkeyResponse {
+    pub pubkey: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SignRequest {
+    /// base58 编码的交易消息
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SignResponse {
+    pub signature: String,
+}
+
+/// 通过 HTTP 访问的签名服务，authority 私钥只保存在签名服务所在的主机上
+pub struct RemoteSigner {
+    http_client: reqwest::Client,
+    url: String,
+    token: Option<String>,
+    pubkey: Pubkey,
+}
+
+impl RemoteSigner {
+    async fn connect(url: &str) -> Result<Self, Error> {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(SIGNER_REQUEST_TIMEOUT))
+            .build()?;
+        let url = url.trim_end_matches('/').to_string();
+        let token = std::env::var(SIGNER_TOKEN_ENV).ok();
+        let resp: PubkeyResponse = with_token(http_client.get(format!("{}/pubkey", url)), &token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let pubkey = Pubkey::from_str(&resp.pubkey)
+            .map_err(|_| Error::Internal(format!("remote signer returned {}", resp.pubkey)))?;
+        Ok(Self {
+            http_client,
+            url,
+            token,
+            pubkey,
+        })
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    /// 请求签名服务签署交易消息，并校验返回的签名
+    pub async fn sign_message(&self, message: &[u8]) -> Result<Signature, Error> {
+        let body = SignRequest {
+            message: bs58::encode(message).into_string(),
+        };
+        let resp = with_token(
+            self.http_client
+                .post(format!("{}/sign", self.url))
+                .json(&body),
+            &self.token,
+        )
+        .send()
+        .await?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(Error::Internal(format!(
+                "remote signer refused to sign ({}): {}",
+                status, body
+            )));
+        }
+        let resp: SignResponse = resp.json().await?;
+        let signature = Signature::from_str(&resp.signature)
+            .map_err(|_| Error::Internal(format!("remote signer returned {}", resp.signature)))?;
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            return Err(Error::Internal(
+                "remote signer returned an invalid signature".to_string(),
+            ));
+        }
+        Ok(signature)
+    }
+}
+
+fn with_token(request: reqwest::RequestBuilder, token: &Option<String>) -> reqwest::RequestBuilder {
+    match token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+/// 连接 --remote-signer 指定的签名服务，之后 authority 由远程签名
+pub async fn connect_remote_signer(url: &str) -> Result<Pubkey, Error> {
+    REMOTE_SIGNER
+        .get_or_try_init(|| RemoteSigner::connect(url))
+        .await
+        .map(|signer| signer.pubkey())
+}
+
+pub fn remote_signer() -> Option<&'static RemoteSigner> {
+    REMOTE_SIGNER.get()
+}
+
+impl Miner {
+    /// 挖矿证明的 authority：配置了远程签名服务时为远程签名者，否则为本地 signer
+    pub fn authority(&self) -> Pubkey {
+        match remote_signer() {
+            Some(remote) => remote.pubkey(),
+            None => self.local_signer().pubkey(),
+        }
+    }
+
+    /// 用交易需要的签名者签署交易：authority 交给远程签名服务，其余使用本地的 signer、本次选择的 fee payer 和额外的 signers
+    pub async fn sign_transaction(
+        &self,
+        tx: &mut Transaction,
+        hash: Hash,
+        fee_payer: &Keypair,
+        extra_signers: &[&Keypair],
+    ) -> Result<(), Error> {
+        tx.message.recent_blockhash = hash;
+        let required: Vec<Pubkey> = tx.message.signer_keys().into_iter().copied().collect();
+        let mut presigners = vec![];
+        if let Some(remote) = remote_signer() {
+            if required.contains(&remote.pubkey()) {
+                let signature = remote.sign_message(&tx.message_data()).await?;
+                presigners.push(Presigner::new(&remote.pubkey(), &signature));
+            }
+        }
+        let signer = self.local_signer();
+        // 指令中引用的默认 fee payer（如 open 的租金支付者）也可能需要签名
+        let default_fee_payer = self.local_fee_payer();
+        let mut signers: Vec<&dyn Signer> = presigners
+            .iter()
+            .map(|presigner| presigner as &dyn Signer)
+            .collect();
+        let local = [fee_payer, &signer, &default_fee_payer];
+        for keypair in local.iter().chain(extra_signers.iter()).copied() {
+            let pubkey = keypair.pubkey();
+            if required.contains(&pubkey) && !signers.iter().any(|s| s.pubkey().eq(&pubkey)) {
+                signers.push(keypair);
+            }
+        }
+        tx.try_sign(&signers, hash)
+            .map_err(|err| Error::Internal(format!("failed to sign transaction: {}", err)))
+    }
+}
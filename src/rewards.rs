@@ -1,16 +1,48 @@
+use colored::*;
+use ore_api::consts::TOKEN_DECIMALS;
+use serde::Serialize;
+use spl_token::amount_to_ui_amount;
+
 use crate::{
+    args::RewardsArgs,
+    output::{machine_output, print_list},
     utils::{amount_u64_to_string, get_config},
     Miner,
 };
 
+/// rewards 的机器可读输出，每个难度一条记录
+#[derive(Serialize)]
+struct RewardRecord {
+    difficulty: u32,
+    reward: f64,
+}
+
 impl Miner {
     // 用于计算和显示奖励信息
-    pub async fn rewards(&self) {
+    pub async fn rewards(&self, args: RewardsArgs) {
+        if let Err(err) = self.select_signer(&args.signer) {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
         // 从RPC客户端获取配置
         let config = get_config(&self.rpc_client).await;
         // 设置基础奖励
         let base_reward_rate = config.base_reward_rate;
 
+        if let Some(format) = machine_output() {
+            let records: Vec<RewardRecord> = (0..32)
+                .map(|i| RewardRecord {
+                    difficulty: config.min_difficulty as u32 + i,
+                    reward: amount_to_ui_amount(
+                        base_reward_rate.saturating_mul(2u64.saturating_pow(i)),
+                        TOKEN_DECIMALS,
+                    ),
+                })
+                .collect();
+            print_list(format, &records);
+            return;
+        }
+
         // 初始化字符串，用于存储奖励信息
         let mut s = format!(
             "{}: {} ORE", // 初始化格式信息
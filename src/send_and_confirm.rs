@@ -8,15 +8,12 @@ use solana_client::{
     client_error::{ClientError, ClientErrorKind, Result as ClientResult},
     rpc_config::RpcSendTransactionConfig,
 };
-use solana_program::{
-    instruction::Instruction,
-    native_token::{lamports_to_sol, sol_to_lamports},
-};
+use solana_program::instruction::Instruction;
 use solana_rpc_client::spinner;
 use solana_sdk::{
     commitment_config::CommitmentLevel,
     compute_budget::ComputeBudgetInstruction,
-    signature::{Signature, Signer},
+    signature::{Keypair, Signature, Signer},
     transaction::Transaction,
 };
 use solana_transaction_status::{TransactionConfirmationStatus, UiTransactionEncoding};
@@ -24,8 +21,6 @@ use solana_transaction_status::{TransactionConfirmationStatus, UiTransactionEnco
 use crate::utils::get_latest_blockhash_with_retries;
 use crate::Miner;
 
-const MIN_SOL_BALANCE: f64 = 0.005;
-
 const RPC_RETRIES: usize = 0;
 const _SIMULATION_RETRIES: usize = 4;
 const GATEWAY_RETRIES: usize = 150;
@@ -45,15 +40,62 @@ impl Miner {
      * 用于发送并确认交易。
      */
     pub async fn send_and_confirm(&self, ixs: &[Instruction], compute_budget: ComputeBudget, skip_confirm: bool) -> ClientResult<Signature> {
+        self.send_and_confirm_with_fee_cap(ixs, compute_budget, skip_confirm, None)
+            .await
+    }
+
+    /// 与 send_and_confirm 相同，但优先费用不超过 max_fee（microlamports）
+    pub async fn send_and_confirm_with_fee_cap(
+        &self,
+        ixs: &[Instruction],
+        compute_budget: ComputeBudget,
+        skip_confirm: bool,
+        max_fee: Option<u64>,
+    ) -> ClientResult<Signature> {
+        self.send_and_confirm_with_signers(ixs, compute_budget, skip_confirm, max_fee, &[])
+            .await
+    }
+
+    /// 与 send_and_confirm_with_fee_cap 相同，但交易还需要 signers 中的密钥对签名（如 sweep 的来源钱包）
+    pub async fn send_and_confirm_with_signers(
+        &self,
+        ixs: &[Instruction],
+        compute_budget: ComputeBudget,
+        skip_confirm: bool,
+        max_fee: Option<u64>,
+        signers: &[&Keypair],
+    ) -> ClientResult<Signature> {
+        self.send_and_confirm_tracked(ixs, compute_budget, skip_confirm, max_fee, signers, &mut vec![])
+            .await
+    }
+
+    /// 与 send_and_confirm_with_signers 相同，并把每次签名得到的交易签名记入 sent；
+    /// 返回错误时已发送的交易仍可能上链，调用方可以据此查询它们的状态
+    pub async fn send_and_confirm_tracked(
+        &self,
+        ixs: &[Instruction],
+        compute_budget: ComputeBudget,
+        skip_confirm: bool,
+        max_fee: Option<u64>,
+        signers: &[&Keypair],
+        sent: &mut Vec<Signature>,
+    ) -> ClientResult<Signature> {
         println!("开始发送并确认交易。。。。。。");
         let progress_bar = spinner::new_progress_bar();
-        let signer = self.signer();
         let client = self.rpc_client.clone();
-        let fee_payer = self.fee_payer();
         let mut send_client = self.rpc_client.clone();
 
-        // 如果余额为零，则返回错误
-        self.check_balance().await;
+        // 选择余额充足的费用支付者，全部耗尽时返回错误
+        let fee_payer = match self.select_fee_payer().await {
+            Ok(fee_payer) => fee_payer,
+            Err(err) => {
+                progress_bar.finish_with_message(format!("{} {}", "ERROR".bold().red(), err));
+                return Err(ClientError {
+                    request: None,
+                    kind: ClientErrorKind::Custom(err.to_string()),
+                });
+            }
+        };
 
         // 创建一个空的向量，用于存储最终的指令预算指令
         let mut final_ixs = vec![];
@@ -74,7 +116,9 @@ impl Miner {
         // 将设置计算单位价格的指令添加到final_ixs向量中
         final_ixs.push(ComputeBudgetInstruction::set_compute_unit_price(
             // 获取优先费用，如果未设置则默认为0
-            self.priority_fee.unwrap_or(0),
+            self.priority_fee
+                .unwrap_or(0)
+                .min(max_fee.unwrap_or(u64::MAX)),
         ));
 
         // 添加用户指令
@@ -104,7 +148,7 @@ impl Miner {
                 println!("开始尝试进行重新签名......!");
                 if self.dynamic_fee { //检查是否使用动态费用
 
-                    let fee = match self.dynamic_fee().await {
+                    let fee = match self.cached_dynamic_fee().await {
                         Ok(fee) => {
                             // 打印获取到的优先费用
                             progress_bar.println(format!("  Priority fee: {} microlamports", fee));
@@ -119,6 +163,7 @@ impl Miner {
                             fee
                         }
                     };
+                    let fee = fee.min(max_fee.unwrap_or(u64::MAX));
                     // 更新计算单位价格指令
                     final_ixs.remove(1); // 移除原有计算单位的指令
                     final_ixs.insert(1, ComputeBudgetInstruction::set_compute_unit_price(fee)); // 添加新的计算单位价格指令
@@ -127,12 +172,15 @@ impl Miner {
 
                 // 重新签名交易
                 let (hash, _slot) = get_latest_blockhash_with_retries(&client).await?;
-                // 根据费用支付者的公钥决定签名
-                if signer.pubkey() == fee_payer.pubkey() {
-                    tx.sign(&[&signer], hash); //使用签名者进行签名
-                } else {
-                    tx.sign(&[&signer, &fee_payer], hash); // 同时使用签名者和费用支付者签名
+                // 使用交易需要的签名者签名，authority 可能由远程签名服务签名
+                if let Err(err) = self.sign_transaction(&mut tx, hash, &fee_payer, signers).await {
+                    progress_bar.finish_with_message(format!("{}: {}", "ERROR".bold().red(), err));
+                    return Err(ClientError {
+                        request: None,
+                        kind: ClientErrorKind::Custom(err.to_string()),
+                    });
                 }
+                sent.push(tx.signatures[0]);
             }
 
             // 发送交易
@@ -248,22 +296,19 @@ impl Miner {
         }
     }
 
-    pub async fn check_balance(&self) {
-        println!("检查余额......");
-        if let Ok(balance) = self.rpc_client.get_balance(&self.fee_payer().pubkey()).await
-        {
-            // 打印当前余额
-            println!("当前余额: {} SOL", lamports_to_sol(balance));
-            if balance <= sol_to_lamports(MIN_SOL_BALANCE) {
-                panic!("{} Insufficient balance: {} SOL\nPlease top up with at least {} SOL",
-                       "ERROR".bold().red(), lamports_to_sol(balance), MIN_SOL_BALANCE);
-            }
-        } else {
-            match self.rpc_client.get_balance(&self.fee_payer().pubkey()).await {
-                Err(e) => println!("无法获取余额，错误信息: {:?}", e),
-                _ => {}
-            }
+    /// 查询已发送的交易，返回其中成功上链的交易签名
+    pub async fn find_landed(&self, signatures: &[Signature]) -> ClientResult<Option<Signature>> {
+        if signatures.is_empty() {
+            return Ok(None);
         }
+        let statuses = self.rpc_client.get_signature_statuses_with_history(signatures).await?.value;
+        Ok(signatures
+            .iter()
+            .zip(statuses)
+            .find_map(|(signature, status)| match status {
+                Some(status) if status.err.is_none() => Some(*signature),
+                _ => None,
+            }))
     }
 }
 
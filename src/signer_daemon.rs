@@ -0,0 +1,456 @@
+use std::{net::SocketAddr, str::FromStr, sync::Arc};
+
+use axum::{
+    extract::State,
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::Local;
+use colored::*;
+use drillx::Solution;
+use ore_api::consts::MINT_ADDRESS;
+use solana_sdk::{
+    compute_budget::{self, ComputeBudgetInstruction},
+    instruction::{CompiledInstruction, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+
+use crate::{
+    args::SignerDaemonArgs,
+    remote_signer::{PubkeyResponse, SignRequest, SignResponse, SIGNER_TOKEN_ENV},
+    utils::proof_pubkey,
+    Miner,
+};
+
+struct SignerState {
+    keypair: Keypair,
+    token: Option<String>,
+    allowed: Vec<AllowedInstruction>,
+    max_cu_price: u64,
+}
+
+/// 允许签署的一种指令：程序相同、数据以 prefix 开头（通常是指令类型的首字节），
+/// 并且 pinned 中的位置必须是 authority 自己的账户
+struct AllowedInstruction {
+    name: &'static str,
+    program_id: Pubkey,
+    prefix: Vec<u8>,
+    pinned: Vec<(usize, Pubkey)>,
+}
+
+impl AllowedInstruction {
+    /// 以 SDK 构建的模板指令定义规则：取模板的程序和数据前 prefix_len 字节，
+    /// pinned 中的账户按其在模板中的位置固定
+    fn from_template(
+        name: &'static str,
+        template: Instruction,
+        prefix_len: usize,
+        pinned: &[Pubkey],
+    ) -> Self {
+        let pinned = pinned
+            .iter()
+            .map(|pubkey| {
+                let index = template
+                    .accounts
+                    .iter()
+                    .position(|meta| meta.pubkey.eq(pubkey))
+                    .expect("Pinned account is not in the template");
+                (index, *pubkey)
+            })
+            .collect();
+        Self {
+            name,
+            program_id: template.program_id,
+            prefix: template.data[..prefix_len].to_vec(),
+            pinned,
+        }
+    }
+
+    fn matches(&self, message: &Message, ix: &CompiledInstruction) -> bool {
+        let program_id = message.account_keys.get(ix.program_id_index as usize);
+        program_id.eq(&Some(&self.program_id))
+            && ix.data.starts_with(&self.prefix)
+            && self.pinned.iter().all(|(index, pubkey)| {
+                ix.accounts
+                    .get(*index)
+                    .and_then(|key_index| message.account_keys.get(*key_index as usize))
+                    .eq(&Some(pubkey))
+            })
+    }
+}
+
+/// 签名服务允许的指令：挖矿、开启和更新证明、领取到 authority 自己的代币账户，以及计算预算
+fn allowed_instructions(authority: Pubkey) -> Vec<AllowedInstruction> {
+    // 模板中与规则无关的账户使用占位地址，避免与需要固定的账户重复
+    let placeholder = Pubkey::new_unique();
+    let beneficiary = get_associated_token_address(&authority, &MINT_ADDRESS);
+    let proof = proof_pubkey(authority);
+    vec![
+        AllowedInstruction::from_template(
+            "set compute unit limit",
+            ComputeBudgetInstruction::set_compute_unit_limit(0),
+            1,
+            &[],
+        ),
+        AllowedInstruction::from_template(
+            "set compute unit price",
+            ComputeBudgetInstruction::set_compute_unit_price(0),
+            1,
+            &[],
+        ),
+        // auth 指令的数据就是证明地址，只允许 authority 自己的证明
+        AllowedInstruction::from_template(
+            "auth",
+            ore_api::instruction::auth(proof),
+            proof.as_ref().len(),
+            &[],
+        ),
+        AllowedInstruction::from_template(
+            "reset",
+            ore_api::instruction::reset(placeholder),
+            1,
+            &[],
+        ),
+        AllowedInstruction::from_template(
+            "mine",
+            ore_api::instruction::mine(
+                placeholder,
+                placeholder,
+                placeholder,
+                Solution::new([0; 16], [0; 8]),
+            ),
+            1,
+            &[],
+        ),
+        AllowedInstruction::from_template(
+            "open",
+            luckycoin_api::sdk::open(placeholder, placeholder, placeholder),
+            1,
+            &[],
+        ),
+        AllowedInstruction::from_template(
+            "update miner",
+            ore_api::instruction::update(placeholder, placeholder),
+            1,
+            &[],
+        ),
+        AllowedInstruction::from_template(
+            "claim",
+            ore_api::instruction::claim(placeholder, beneficiary, 0),
+            1,
+            &[beneficiary],
+        ),
+        AllowedInstruction::from_template(
+            "pool claim",
+            ore_pool_api::sdk::claim(placeholder, beneficiary, placeholder, 0, 0),
+            1,
+            &[beneficiary],
+        ),
+        AllowedInstruction::from_template(
+            "create token account",
+            create_associated_token_account_idempotent(
+                &placeholder,
+                &authority,
+                &MINT_ADDRESS,
+                &spl_token::id(),
+            ),
+            1,
+            &[authority],
+        ),
+    ]
+}
+
+/// 解析 set_compute_unit_price 指令中的价格（microlamports）
+fn compute_unit_price(message: &Message, ix: &CompiledInstruction) -> Option<u64> {
+    let program_id = message.account_keys.get(ix.program_id_index as usize)?;
+    let discriminator = ComputeBudgetInstruction::set_compute_unit_price(0).data[0];
+    if program_id.ne(&compute_budget::id()) || ix.data.first().ne(&Some(&discriminator)) {
+        return None;
+    }
+    let price: [u8; 8] = ix.data.get(1..9)?.try_into().ok()?;
+    Some(u64::from_le_bytes(price))
+}
+
+/// 检查消息中的每条指令，返回各指令匹配的规则名称，或拒绝签名的原因
+fn check_message(state: &SignerState, message: &Message) -> Result<Vec<&'static str>, String> {
+    let mut names = vec![];
+    for (i, ix) in message.instructions.iter().enumerate() {
+        let Some(rule) = state.allowed.iter().find(|rule| rule.matches(message, ix)) else {
+            let program_id = message
+                .account_keys
+                .get(ix.program_id_index as usize)
+                .copied()
+                .unwrap_or_default();
+            return Err(format!(
+                "instruction {} (program {}) is not allowed",
+                i, program_id
+            ));
+        };
+        if let Some(price) = compute_unit_price(message, ix) {
+            if price.gt(&state.max_cu_price) {
+                return Err(format!(
+                    "compute unit price {} exceeds the cap of {} microlamports",
+                    price, state.max_cu_price
+                ));
+            }
+        }
+        names.push(rule.name);
+    }
+    Ok(names)
+}
+
+impl Miner {
+    // 运行参考签名服务：持有 authority 私钥，只为调用允许的程序的交易签名，
+    // 矿机通过 --remote-signer 连接后即可在不保存 authority 私钥的情况下挖矿和领取
+    pub async fn signer_daemon(&self, args: SignerDaemonArgs) {
+        if let Err(err) = self.select_signer(&args.signer) {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
+        let keypair = self.local_signer();
+        // 允许的指令中包括 update-miner，没有令牌时任何本地进程都能改走挖矿权限，必须显式允许
+        let token = std::env::var(SIGNER_TOKEN_ENV)
+            .ok()
+            .filter(|token| !token.is_empty());
+        if token.is_none() {
+            if !args.allow_no_token {
+                eprintln!(
+                    "{} {} is not set. Set it, or pass --allow-no-token to let any local process request signatures.",
+                    "ERROR".bold().red(),
+                    SIGNER_TOKEN_ENV
+                );
+                return;
+            }
+            println!(
+                "{} {} is not set. Any local process can request signatures.",
+                "WARNING".bold().yellow(),
+                SIGNER_TOKEN_ENV
+            );
+        }
+        println!("Signing for {}", keypair.pubkey());
+        println!(
+            "Refusing compute unit prices above {} microlamports",
+            args.max_cu_price
+        );
+        let state = Arc::new(SignerState {
+            allowed: allowed_instructions(keypair.pubkey()),
+            keypair,
+            token,
+            max_cu_price: args.max_cu_price,
+        });
+        let app = Router::new()
+            .route("/pubkey", get(pubkey))
+            .route("/sign", post(sign))
+            .with_state(state);
+        let addr = SocketAddr::from_str(&format!("{}:{}", args.host, args.port))
+            .expect("Failed to parse signer address");
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .expect("Failed to bind signer address");
+        println!("Signer listening on http://{}", addr);
+        axum::serve(listener, app)
+            .await
+            .expect("Signer server failed");
+    }
+}
+
+fn check_token(state: &SignerState, headers: &HeaderMap) -> Result<(), (StatusCode, String)> {
+    let Some(ref token) = state.token else {
+        return Ok(());
+    };
+    let authorized = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map_or(false, |value| {
+            constant_time_eq(value.as_bytes(), token.as_bytes())
+        });
+    if !authorized {
+        return Err((StatusCode::UNAUTHORIZED, "invalid token".to_string()));
+    }
+    Ok(())
+}
+
+// 逐字节比较全部内容，耗时不随第一个不同字节的位置变化，避免按时间猜出令牌
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len().ne(&b.len()) {
+        return false;
+    }
+    a.iter()
+        .zip(b.iter())
+        .fold(0u8, |diff, (x, y)| diff | (x ^ y))
+        == 0
+}
+
+async fn pubkey(
+    State(state): State<Arc<SignerState>>,
+    headers: HeaderMap,
+) -> Result<Json<PubkeyResponse>, (StatusCode, String)> {
+    check_token(&state, &headers)?;
+    Ok(Json(PubkeyResponse {
+        pubkey: state.keypair.pubkey().to_string(),
+    }))
+}
+
+async fn sign(
+    State(state): State<Arc<SignerState>>,
+    headers: HeaderMap,
+    Json(request): Json<SignRequest>,
+) -> Result<Json<SignResponse>, (StatusCode, String)> {
+    check_token(&state, &headers)?;
+    let data = bs58::decode(&request.message).into_vec().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            "invalid message encoding".to_string(),
+        )
+    })?;
+    let message: Message = bincode::deserialize(&data)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "invalid message".to_string()))?;
+
+    // 只签署允许的指令，领取必须转入 authority 自己的代币账户，拒绝转账等其他操作
+    let names = check_message(&state, &message).map_err(|reason| {
+        println!(
+            "{} Refused to sign: {}",
+            Local::now().format("%Y-%m-%d %H:%M:%S"),
+            reason
+        );
+        (StatusCode::FORBIDDEN, reason)
+    })?;
+    let signature = state.keypair.sign_message(&data);
+    println!(
+        "{} Signed {}: {}",
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        names.join(", "),
+        signature
+    );
+    Ok(Json(SignResponse {
+        signature: signature.to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::system_instruction;
+
+    use super::*;
+
+    fn signer_state() -> SignerState {
+        let keypair = Keypair::new();
+        SignerState {
+            allowed: allowed_instructions(keypair.pubkey()),
+            keypair,
+            token: None,
+            max_cu_price: 1_000,
+        }
+    }
+
+    fn check(state: &SignerState, ixs: &[Instruction]) -> Result<Vec<&'static str>, String> {
+        let message = Message::new(ixs, Some(&state.keypair.pubkey()));
+        check_message(state, &message)
+    }
+
+    #[test]
+    fn signs_mining_and_claims_to_the_authority() {
+        let state = signer_state();
+        let authority = state.keypair.pubkey();
+        let beneficiary = get_associated_token_address(&authority, &MINT_ADDRESS);
+        let mine = [
+            ComputeBudgetInstruction::set_compute_unit_limit(500_000),
+            ComputeBudgetInstruction::set_compute_unit_price(1_000),
+            ore_api::instruction::auth(proof_pubkey(authority)),
+            ore_api::instruction::mine(
+                authority,
+                authority,
+                Pubkey::new_unique(),
+                Solution::new([1; 16], [2; 8]),
+            ),
+        ];
+        assert_eq!(
+            check(&state, &mine).unwrap(),
+            vec![
+                "set compute unit limit",
+                "set compute unit price",
+                "auth",
+                "mine"
+            ]
+        );
+        let claim = [
+            create_associated_token_account_idempotent(
+                &authority,
+                &authority,
+                &MINT_ADDRESS,
+                &spl_token::id(),
+            ),
+            ore_api::instruction::claim(authority, beneficiary, 100),
+        ];
+        assert!(check(&state, &claim).is_ok());
+    }
+
+    #[test]
+    fn refuses_other_beneficiaries_and_programs() {
+        let state = signer_state();
+        let authority = state.keypair.pubkey();
+        let other = Pubkey::new_unique();
+        let other_tokens = get_associated_token_address(&other, &MINT_ADDRESS);
+        assert!(check(
+            &state,
+            &[ore_api::instruction::claim(authority, other_tokens, 100)]
+        )
+        .is_err());
+        assert!(check(&state, &[ore_api::instruction::auth(proof_pubkey(other))]).is_err());
+        assert!(check(
+            &state,
+            &[create_associated_token_account_idempotent(
+                &authority,
+                &other,
+                &MINT_ADDRESS,
+                &spl_token::id(),
+            )]
+        )
+        .is_err());
+        assert!(check(
+            &state,
+            &[system_instruction::transfer(&authority, &other, 1)]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn requires_the_exact_token() {
+        let mut state = signer_state();
+        state.token = Some("secret".to_string());
+        let headers = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(AUTHORIZATION, value.parse().unwrap());
+            headers
+        };
+        assert!(check_token(&state, &headers("Bearer secret")).is_ok());
+        for value in ["Bearer secreT", "Bearer secre", "Bearer secrets", "secret"] {
+            let err = check_token(&state, &headers(value)).unwrap_err();
+            assert_eq!(err.0, StatusCode::UNAUTHORIZED);
+        }
+        assert!(check_token(&state, &HeaderMap::new()).is_err());
+    }
+
+    #[test]
+    fn caps_the_compute_unit_price() {
+        let state = signer_state();
+        assert!(check(
+            &state,
+            &[ComputeBudgetInstruction::set_compute_unit_price(1_000)]
+        )
+        .is_ok());
+        assert!(check(
+            &state,
+            &[ComputeBudgetInstruction::set_compute_unit_price(1_001)]
+        )
+        .is_err());
+    }
+}
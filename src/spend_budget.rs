@@ -0,0 +1,117 @@
+use std::{fs, path::PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use solana_program::native_token::sol_to_lamports;
+
+use crate::{
+    args::{BudgetAction, MineArgs},
+    utils::config_path,
+};
+
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct SpendEntry {
+    timestamp: i64,
+    lamports: u64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct SpendLedger {
+    entries: Vec<SpendEntry>,
+}
+
+/// 挖矿的 SOL 花费预算，花费记录持久化到磁盘，重启后继续生效
+pub struct SpendBudget {
+    path: PathBuf,
+    ledger: SpendLedger,
+    per_round: Option<u64>,
+    per_hour: Option<u64>,
+    per_day: Option<u64>,
+    pub action: BudgetAction,
+}
+
+impl SpendBudget {
+    /// 根据挖矿参数创建预算，未配置任何限额时返回 None
+    pub fn from_args(args: &MineArgs) -> Option<Self> {
+        if args.max_sol_per_round.is_none()
+            && args.max_sol_per_hour.is_none()
+            && args.max_sol_per_day.is_none()
+        {
+            return None;
+        }
+        let path = args
+            .budget_file
+            .clone()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| config_path("spend.json"));
+
+        // 读取已有的花费记录，文件不存在或无法解析时从零开始
+        let ledger = fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Some(Self {
+            path,
+            ledger,
+            per_round: args.max_sol_per_round.map(sol_to_lamports),
+            per_hour: args.max_sol_per_hour.map(sol_to_lamports),
+            per_day: args.max_sol_per_day.map(sol_to_lamports),
+            action: args.budget_exhausted,
+        })
+    }
+
+    /// 单笔交易的花费上限（lamports）
+    pub fn round_limit(&self) -> Option<u64> {
+        self.per_round
+    }
+
+    /// 最近一段时间内的花费（lamports）
+    pub fn spent_since(&self, seconds: i64) -> u64 {
+        let since = Utc::now().timestamp().saturating_sub(seconds);
+        self.ledger
+            .entries
+            .iter()
+            .filter(|entry| entry.timestamp.gt(&since))
+            .map(|entry| entry.lamports)
+            .sum()
+    }
+
+    /// 每小时或每天的预算是否已经用尽
+    pub fn exhausted(&self) -> bool {
+        let hour_exhausted = self
+            .per_hour
+            .map_or(false, |limit| self.spent_since(HOUR).ge(&limit));
+        let day_exhausted = self
+            .per_day
+            .map_or(false, |limit| self.spent_since(DAY).ge(&limit));
+        hour_exhausted || day_exhausted
+    }
+
+    /// 记录一笔交易的实际花费，并写回磁盘
+    pub fn record(&mut self, lamports: u64) {
+        let now = Utc::now().timestamp();
+        self.ledger
+            .entries
+            .retain(|entry| entry.timestamp.gt(&now.saturating_sub(DAY)));
+        if lamports.gt(&0) {
+            self.ledger.entries.push(SpendEntry {
+                timestamp: now,
+                lamports,
+            });
+        }
+        if let Err(err) = self.save() {
+            println!("Failed to persist SOL spending to {:?}: {}", self.path, err);
+        }
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_string(&self.ledger)?;
+        fs::write(&self.path, data)
+    }
+}
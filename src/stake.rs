@@ -1,5 +1,6 @@
 use std::str::FromStr;
 
+use colored::*;
 use solana_program::pubkey::Pubkey;
 use solana_sdk::signature::Signer;
 
@@ -11,8 +12,12 @@ use crate::{
 impl Miner {
     // 用于进行质押操作
     pub async fn stake(&self, args: StakeArgs) {
+        if let Err(err) = self.select_signer(&args.signer) {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
         // 获取签名者(矿工的公钥)
-        let signer = self.signer();
+        let signer = self.local_signer();
         // 确定发送者的代币账户地址
         let sender = match args.token_account {
             // 如果提供了代币账户地址，则将其解析为 Pubkey
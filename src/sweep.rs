@@ -0,0 +1,318 @@
+use std::str::FromStr;
+
+use colored::*;
+use ore_api::consts::{MINT_ADDRESS, TOKEN_DECIMALS};
+use solana_program::{
+    native_token::{lamports_to_sol, sol_to_lamports},
+    pubkey::Pubkey,
+    system_instruction,
+};
+use solana_sdk::signature::{Keypair, Signer};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use spl_token::amount_to_ui_amount;
+
+use crate::{
+    args::SweepArgs,
+    balance::BalanceRow,
+    cu_limits::CU_LIMIT_CLAIM,
+    error::Error,
+    fee_payers::configure_fee_payers,
+    keystore::{list_wallets, unlock_wallets},
+    mnemonic::load_keypair,
+    send_and_confirm::ComputeBudget,
+    top_up::configure_top_up,
+    utils::{amount_u64_to_string, ask_confirm},
+    Miner,
+};
+
+// 创建目标代币账户、转账 ORE 和 SOL 所需的计算单位
+const CU_SWEEP_TRANSFERS: u32 = 40_000;
+
+// 0 字节系统账户的免租金最低余额（lamports）
+const RENT_EXEMPT_MINIMUM: u64 = 890_880;
+
+/// 一个来源钱包要归集的金额
+struct SweepPlan {
+    label: String,
+    keypair: Keypair,
+    claim: u64,
+    transfer: u64,
+    sol: u64,
+}
+
+impl Miner {
+    // 把多个钱包的证明余额领取到目标钱包，并转入各钱包代币账户中的 ORE（可选转入多余的 SOL），最后逐个钱包报告结果
+    pub async fn sweep(&self, args: SweepArgs) -> Result<(), Error> {
+        self.select_signer(&args.signer)?;
+        configure_fee_payers(&args.fees.fee_payers, args.fees.fee_payer_strategy)?;
+        configure_top_up(&args.top_up)?;
+        let destination = match args.to {
+            Some(ref to) => Pubkey::from_str(to)
+                .map_err(|_| Error::Internal(format!("Invalid address: {}", to)))?,
+            None => self.local_signer().pubkey(),
+        };
+        let destination_tokens = get_associated_token_address(&destination, &MINT_ADDRESS);
+
+        // 加载来源钱包，跳过目标钱包本身和重复的钱包
+        let mut sources: Vec<(String, Keypair)> = vec![];
+        for path in args.keypairs.iter() {
+            sources.push((path.clone(), load_keypair(path, None)?));
+        }
+        if args.all_wallets {
+            // 钱包库中的钱包使用同一个口令，只输入一次
+            let wallets = list_wallets();
+            let keypairs = unlock_wallets(&wallets)?;
+            for (wallet, keypair) in wallets.into_iter().zip(keypairs) {
+                sources.push((wallet.name, keypair));
+            }
+        }
+        let sources = dedupe_sources(sources, &destination);
+        if sources.is_empty() {
+            return Err(Error::Internal("no wallets to sweep".to_string()));
+        }
+
+        // 批量查询余额，计算每个钱包要领取和转出的金额
+        let pubkeys: Vec<Pubkey> = sources
+            .iter()
+            .map(|(_, keypair)| keypair.pubkey())
+            .collect();
+        let balances = self.get_balances(&pubkeys).await?;
+        let plans = plan_sweep(
+            sources,
+            &balances,
+            args.sol.then(|| sol_to_lamports(args.keep_sol)),
+            &self.fee_payer_pubkeys(),
+        )?;
+
+        let total_ore: u64 = plans.iter().map(|plan| plan.claim + plan.transfer).sum();
+        let total_sol: u64 = plans.iter().map(|plan| plan.sol).sum();
+        if total_ore.eq(&0) && total_sol.eq(&0) {
+            println!("Nothing to sweep from {} wallets", plans.len());
+            return Ok(());
+        }
+        if !ask_confirm(
+            format!(
+                "\nYou are about to sweep {} and {} from {} wallets into {}.\n\nAre you sure you want to continue? [Y/n]",
+                format!("{} ORE", amount_to_ui_amount(total_ore, TOKEN_DECIMALS)).bold(),
+                format!("{} SOL", lamports_to_sol(total_sol)).bold(),
+                plans.len(),
+                destination
+            )
+            .as_str(),
+        ) {
+            return Ok(());
+        }
+
+        // 逐个钱包发送：领取到目标代币账户、转入代币账户中的 ORE 和多余的 SOL
+        let mut results = vec![];
+        for plan in plans.iter() {
+            let source = plan.keypair.pubkey();
+            if plan.claim.eq(&0) && plan.transfer.eq(&0) && plan.sol.eq(&0) {
+                results.push("nothing to sweep".to_string());
+                continue;
+            }
+            println!("Sweeping {} ({})", plan.label, source);
+            let mut ixs = vec![];
+            if plan.claim.gt(&0) || plan.transfer.gt(&0) {
+                ixs.push(create_associated_token_account_idempotent(
+                    &self.local_signer().pubkey(),
+                    &destination,
+                    &MINT_ADDRESS,
+                    &spl_token::id(),
+                ));
+            }
+            if plan.claim.gt(&0) {
+                ixs.push(ore_api::instruction::claim(
+                    source,
+                    destination_tokens,
+                    plan.claim,
+                ));
+            }
+            if plan.transfer.gt(&0) {
+                ixs.push(
+                    spl_token::instruction::transfer(
+                        &spl_token::id(),
+                        &get_associated_token_address(&source, &MINT_ADDRESS),
+                        &destination_tokens,
+                        &source,
+                        &[&source],
+                        plan.transfer,
+                    )
+                    .expect("Failed to create transfer instruction"),
+                );
+            }
+            if plan.sol.gt(&0) {
+                ixs.push(system_instruction::transfer(
+                    &source,
+                    &destination,
+                    plan.sol,
+                ));
+            }
+            let result = self
+                .send_and_confirm_with_signers(
+                    &ixs,
+                    ComputeBudget::Fixed(CU_LIMIT_CLAIM.saturating_add(CU_SWEEP_TRANSFERS)),
+                    false,
+                    None,
+                    &[&plan.keypair],
+                )
+                .await;
+            results.push(match result {
+                Ok(signature) => signature.to_string(),
+                Err(err) => format!("{} {}", "FAILED".bold().red(), err),
+            });
+        }
+
+        // 逐个钱包报告结果
+        println!(
+            "\n  {:<20} {:<44} {:>16} {:>16} {:>12}  {}",
+            "Wallet", "Address", "Claimed (ORE)", "Moved (ORE)", "SOL", "Result"
+        );
+        for (plan, result) in plans.iter().zip(results.iter()) {
+            println!(
+                "  {:<20} {:<44} {:>16} {:>16} {:>12}  {}",
+                plan.label,
+                plan.keypair.pubkey().to_string(),
+                amount_u64_to_string(plan.claim),
+                amount_u64_to_string(plan.transfer),
+                lamports_to_sol(plan.sol),
+                result
+            );
+        }
+        Ok(())
+    }
+}
+
+// 跳过目标钱包本身和重复的钱包，保留第一次出现的顺序
+fn dedupe_sources(
+    mut sources: Vec<(String, Keypair)>,
+    destination: &Pubkey,
+) -> Vec<(String, Keypair)> {
+    let mut seen = vec![*destination];
+    sources.retain(|(_, keypair)| {
+        let pubkey = keypair.pubkey();
+        if seen.contains(&pubkey) {
+            return false;
+        }
+        seen.push(pubkey);
+        true
+    });
+    sources
+}
+
+// 计算每个钱包要领取和转出的金额；keep_sol 为 None 时不转出 SOL，费用支付者的 SOL 留给交易手续费。
+// 系统账户的余额只能为 0 或不低于免租金的最低余额，保留介于两者之间的金额会让转账失败
+fn plan_sweep(
+    sources: Vec<(String, Keypair)>,
+    balances: &[BalanceRow],
+    keep_sol: Option<u64>,
+    fee_payers: &[Pubkey],
+) -> Result<Vec<SweepPlan>, Error> {
+    if let Some(keep_sol) = keep_sol {
+        if keep_sol.gt(&0) && keep_sol.lt(&RENT_EXEMPT_MINIMUM) {
+            return Err(Error::Internal(format!(
+                "--keep-sol must be 0 to sweep everything, or at least the rent-exempt minimum of {} SOL",
+                lamports_to_sol(RENT_EXEMPT_MINIMUM)
+            )));
+        }
+    }
+    Ok(sources
+        .into_iter()
+        .zip(balances.iter())
+        .map(|((label, keypair), balance)| {
+            let sol = match keep_sol {
+                Some(keep_sol) if !fee_payers.contains(&balance.address) => {
+                    balance.sol.saturating_sub(keep_sol)
+                }
+                _ => 0,
+            };
+            SweepPlan {
+                label,
+                keypair,
+                claim: balance.stake,
+                transfer: balance.ore,
+                sol,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn copy(keypair: &Keypair) -> Keypair {
+        Keypair::from_bytes(&keypair.to_bytes()).unwrap()
+    }
+
+    fn balance(address: Pubkey, sol: u64) -> BalanceRow {
+        BalanceRow {
+            address,
+            sol,
+            ore: 300,
+            stake: 200,
+            multiplier: 1.0,
+            last_hash_at: None,
+        }
+    }
+
+    #[test]
+    fn sweep_skips_destination_and_duplicates() {
+        let destination = Keypair::new();
+        let a = Keypair::new();
+        let b = Keypair::new();
+        let sources = vec![
+            ("a".to_string(), copy(&a)),
+            ("destination".to_string(), copy(&destination)),
+            ("a again".to_string(), copy(&a)),
+            ("b".to_string(), copy(&b)),
+        ];
+        let labels: Vec<String> = dedupe_sources(sources, &destination.pubkey())
+            .into_iter()
+            .map(|(label, _)| label)
+            .collect();
+        assert_eq!(labels, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn sweep_plans_keep_sol_and_skip_fee_payers() {
+        let (a, b, c) = (Keypair::new(), Keypair::new(), Keypair::new());
+        let balances = vec![
+            balance(a.pubkey(), 2_000_000),
+            balance(b.pubkey(), 2_000_000),
+            balance(c.pubkey(), 500_000),
+        ];
+        let sources = || {
+            vec![
+                ("a".to_string(), copy(&a)),
+                ("b".to_string(), copy(&b)),
+                ("c".to_string(), copy(&c)),
+            ]
+        };
+
+        // b 是费用支付者，c 的余额低于保留金额
+        let plans = plan_sweep(sources(), &balances, Some(1_000_000), &[b.pubkey()]).unwrap();
+        let sol: Vec<u64> = plans.iter().map(|plan| plan.sol).collect();
+        assert_eq!(sol, vec![1_000_000, 0, 0]);
+        assert!(plans
+            .iter()
+            .all(|plan| plan.claim.eq(&200) && plan.transfer.eq(&300)));
+
+        // 保留 0 时转出全部 SOL
+        let plans = plan_sweep(sources(), &balances, Some(0), &[]).unwrap();
+        let sol: Vec<u64> = plans.iter().map(|plan| plan.sol).collect();
+        assert_eq!(sol, vec![2_000_000, 2_000_000, 500_000]);
+
+        // 低于免租金最低余额的保留金额会让账户无法存续
+        for keep_sol in [1, 400, RENT_EXEMPT_MINIMUM - 1] {
+            assert!(plan_sweep(sources(), &balances, Some(keep_sol), &[]).is_err());
+        }
+        assert!(plan_sweep(sources(), &balances, Some(RENT_EXEMPT_MINIMUM), &[]).is_ok());
+
+        // 没有 --sol 时不转出 SOL
+        let plans = plan_sweep(sources(), &balances, None, &[]).unwrap();
+        assert!(plans.iter().all(|plan| plan.sol.eq(&0)));
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    collections::HashSet,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+};
+
+use chrono::Utc;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use solana_program::{
+    native_token::{lamports_to_sol, sol_to_lamports},
+    system_instruction,
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use tokio::sync::Mutex;
+
+use crate::{args::TopUpArgs, error::Error, mnemonic::load_keypair, utils::config_path, Miner};
+
+const TOP_UP_LOG_FILE: &str = "top-ups.jsonl";
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+// 补充交易只有资金钱包一个签名（lamports）
+const TOP_UP_FEE: u64 = 5_000;
+
+// 配置 --funding-wallet 后，余额不足的费用支付者自动从资金钱包补充 SOL
+static TOP_UP: OnceLock<TopUp> = OnceLock::new();
+
+struct TopUp {
+    funding: Keypair,
+    threshold: u64,
+    amount: u64,
+    daily_cap: u64,
+    dry_run: bool,
+    log_file: PathBuf,
+    // 同一时间只进行一次补充，避免并发交易重复转账；同时记录已打印过 dry run 的费用支付者
+    lock: Mutex<HashSet<Pubkey>>,
+    // 本进程补充交易的手续费总额（lamports）
+    fees: AtomicU64,
+}
+
+/// 一次补充的本地记录
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TopUpRecord {
+    pub timestamp: i64,
+    pub funding: String,
+    pub recipient: String,
+    pub lamports: u64,
+    pub dry_run: bool,
+    pub signature: Option<String>,
+}
+
+/// 加载 --funding-wallet 指定的资金钱包，之后 send_and_confirm 在选择费用支付者前自动补充余额
+pub fn configure_top_up(args: &TopUpArgs) -> Result<(), Error> {
+    let Some(ref funding_wallet) = args.funding_wallet else {
+        return Ok(());
+    };
+    let funding = load_keypair(funding_wallet, None)?;
+    let log_file = args
+        .top_up_log
+        .as_ref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| config_path(TOP_UP_LOG_FILE));
+    println!(
+        "Topping up fee payers below {} SOL with {} SOL from {} (daily cap {} SOL{})",
+        args.top_up_threshold,
+        args.top_up_amount,
+        funding.pubkey(),
+        args.top_up_daily_cap,
+        if args.top_up_dry_run { ", dry run" } else { "" }
+    );
+    TOP_UP
+        .set(TopUp {
+            funding,
+            threshold: sol_to_lamports(args.top_up_threshold),
+            amount: sol_to_lamports(args.top_up_amount),
+            daily_cap: sol_to_lamports(args.top_up_daily_cap),
+            dry_run: args.top_up_dry_run,
+            log_file,
+            lock: Mutex::new(HashSet::new()),
+            fees: AtomicU64::new(0),
+        })
+        .map_err(|_| Error::Internal("funding wallet is already configured".to_string()))
+}
+
+/// 本进程补充交易的手续费总额（lamports），未配置资金钱包时为 0
+pub fn top_up_fees() -> u64 {
+    TOP_UP
+        .get()
+        .map_or(0, |top_up| top_up.fees.load(Ordering::Relaxed))
+}
+
+impl Miner {
+    /// 为余额低于阈值的费用支付者补充 SOL，成功后更新 balances；补充失败只打印警告
+    pub async fn top_up_fee_payers(&self, pubkeys: &[Pubkey], balances: &mut [u64]) {
+        let Some(top_up) = TOP_UP.get() else {
+            return;
+        };
+        if balances.iter().all(|balance| balance.ge(&top_up.threshold)) {
+            return;
+        }
+        let mut dry_runs = top_up.lock.lock().await;
+        for (pubkey, balance) in pubkeys.iter().zip(balances.iter_mut()) {
+            if balance.ge(&top_up.threshold) || pubkey.eq(&top_up.funding.pubkey()) {
+                continue;
+            }
+
+            // 按当天已补充的总额限制本次补充
+            let spent = spent_today(&top_up.log_file);
+            let lamports = top_up.amount.min(top_up.daily_cap.saturating_sub(spent));
+            if lamports.eq(&0) {
+                println!(
+                    "{} Daily top-up cap of {} SOL reached, not topping up {}",
+                    "WARNING".bold().yellow(),
+                    lamports_to_sol(top_up.daily_cap),
+                    pubkey
+                );
+                return;
+            }
+
+            // dry run 对每个费用支付者只记录一次，避免每笔交易重复输出
+            if top_up.dry_run {
+                if !dry_runs.insert(*pubkey) {
+                    continue;
+                }
+                println!(
+                    "Dry run: would top up {} ({} SOL) with {} SOL",
+                    pubkey,
+                    lamports_to_sol(*balance),
+                    lamports_to_sol(lamports)
+                );
+                record_top_up(
+                    &top_up.log_file,
+                    &TopUpRecord::new(&top_up.funding, pubkey, lamports, true, None),
+                );
+                continue;
+            }
+
+            match self.send_top_up(&top_up.funding, pubkey, lamports).await {
+                Ok(signature) => {
+                    println!(
+                        "Topped up {} with {} SOL: {}",
+                        pubkey,
+                        lamports_to_sol(lamports),
+                        signature
+                    );
+                    record_top_up(
+                        &top_up.log_file,
+                        &TopUpRecord::new(
+                            &top_up.funding,
+                            pubkey,
+                            lamports,
+                            false,
+                            Some(signature),
+                        ),
+                    );
+                    *balance = balance.saturating_add(lamports);
+                    top_up.fees.fetch_add(TOP_UP_FEE, Ordering::Relaxed);
+                }
+                Err(err) => {
+                    println!(
+                        "{} Failed to top up {} from {}: {}",
+                        "WARNING".bold().yellow(),
+                        pubkey,
+                        top_up.funding.pubkey(),
+                        err
+                    );
+                }
+            }
+        }
+    }
+
+    // 补充交易由资金钱包自己支付手续费，不经过 send_and_confirm 的费用支付者选择
+    async fn send_top_up(
+        &self,
+        funding: &Keypair,
+        recipient: &Pubkey,
+        lamports: u64,
+    ) -> Result<String, Error> {
+        let ix = system_instruction::transfer(&funding.pubkey(), recipient, lamports);
+        let blockhash = self.rpc_client.get_latest_blockhash().await?;
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&funding.pubkey()),
+            &[funding],
+            blockhash,
+        );
+        let signature = self.rpc_client.send_and_confirm_transaction(&tx).await?;
+        Ok(signature.to_string())
+    }
+}
+
+impl TopUpRecord {
+    fn new(
+        funding: &Keypair,
+        recipient: &Pubkey,
+        lamports: u64,
+        dry_run: bool,
+        signature: Option<String>,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now().timestamp(),
+            funding: funding.pubkey().to_string(),
+            recipient: recipient.to_string(),
+            lamports,
+            dry_run,
+            signature,
+        }
+    }
+}
+
+/// 追加一条补充记录，写入失败只打印提示
+fn record_top_up(path: &PathBuf, record: &TopUpRecord) {
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| {
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{}", serde_json::to_string(record)?)
+        });
+    if let Err(err) = result {
+        println!("Failed to record top-up to {:?}: {}", path, err);
+    }
+}
+
+/// 当天（UTC）已实际转出的补充总额，重启后从记录中恢复
+fn spent_today(path: &PathBuf) -> u64 {
+    let Ok(data) = fs::read_to_string(path) else {
+        return 0;
+    };
+    let today = Utc::now().timestamp().div_euclid(SECONDS_PER_DAY);
+    data.lines()
+        .filter_map(|line| serde_json::from_str::<TopUpRecord>(line).ok())
+        .filter(|record| !record.dry_run && record.timestamp.div_euclid(SECONDS_PER_DAY).eq(&today))
+        .map(|record| record.lamports)
+        .sum()
+}
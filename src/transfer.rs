@@ -9,7 +9,9 @@ use spl_token::amount_to_ui_amount;
 use crate::{
     args::TransferArgs,
     cu_limits::CU_LIMIT_CLAIM,
+    fee_payers::configure_fee_payers,
     send_and_confirm::ComputeBudget,
+    top_up::configure_top_up,
     utils::{amount_f64_to_u64, ask_confirm},
     Miner,
 };
@@ -17,8 +19,32 @@ use crate::{
 impl Miner {
     // 用于转账操作
     pub async fn transfer(&self, args: TransferArgs) {
+        if let Err(err) = self.select_signer(&args.signer) {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
+        if let Err(err) = configure_fee_payers(&args.fees.fee_payers, args.fees.fee_payer_strategy)
+        {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
+        if let Err(err) = configure_top_up(&args.top_up) {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
+        // 按 CSV 文件批量转账
+        if let Some(ref batch) = args.batch {
+            if let Err(err) = self.transfer_batch(batch, &args).await {
+                println!("{} {}", "ERROR".bold().red(), err);
+            }
+            return;
+        }
+        let (Some(amount), Some(to)) = (args.amount, args.to) else {
+            return;
+        };
+
         // 获取签名者(矿工公钥)
-        let signer = self.signer();
+        let signer = self.local_signer();
         let pubkey = signer.pubkey(); // 获取签名者公钥
         // 计算发送者的代币账户地址
         let sender_tokens =
@@ -26,7 +52,7 @@ impl Miner {
         let mut ixs = vec![]; // 存储交易指令的向量
 
         // 初始化接受者的地址
-        let to = Pubkey::from_str(&args.to).expect("Failed to parse recipient wallet address");
+        let to = Pubkey::from_str(&to).expect("Failed to parse recipient wallet address");
         // 计算接受者的代币账户地址
         let recipient_tokens =
             spl_associated_token_account::get_associated_token_address(&to, &MINT_ADDRESS);
@@ -49,7 +75,7 @@ impl Miner {
         }
 
         // 解析转账金额
-        let amount = amount_f64_to_u64(args.amount);
+        let amount = amount_f64_to_u64(amount);
 
         // 确认用户是否继续转账
         if !ask_confirm(
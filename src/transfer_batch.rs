@@ -0,0 +1,755 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use chrono::Utc;
+use colored::*;
+use ore_api::consts::{MINT_ADDRESS, TOKEN_DECIMALS};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    packet::PACKET_DATA_SIZE,
+    signature::{Signature, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use spl_token::amount_to_ui_amount;
+
+use crate::{
+    args::TransferArgs,
+    error::Error,
+    utils::{amount_f64_to_u64, amount_u64_to_string, ask_confirm, MAX_ACCOUNTS_PER_REQUEST},
+    Miner,
+};
+
+// 每条指令的计算单位估计
+const CU_TRANSFER: u32 = 5_000;
+const CU_CREATE_ATA: u32 = 30_000;
+const CU_MARGIN: u32 = 10_000;
+
+const CONFIRM_DELAY: u64 = 2_000; // 查询交易状态的间隔（毫秒）
+const MAX_SIGNING_ATTEMPTS: usize = 5; // 每笔交易最多使用的区块哈希数
+
+/// 批量文件中的一笔转账，row 为文件中的行号
+#[derive(Clone, Debug, PartialEq)]
+struct Payout {
+    row: u64,
+    recipient: Pubkey,
+    amount: u64,
+}
+
+/// 打包进同一笔交易的转账
+struct PayoutTx {
+    payouts: Vec<Payout>,
+    ixs: Vec<Instruction>,
+    compute_units: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum PayoutStatus {
+    /// 交易已发送但尚未确认，结果未知
+    Pending,
+    /// 交易已确认
+    Sent,
+}
+
+/// 进度文件中的一条记录，同一行以最后一条为准
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct PayoutProgress {
+    row: u64,
+    recipient: String,
+    amount: u64,
+    status: PayoutStatus,
+    signature: String,
+    /// 交易区块哈希的最后有效区块高度，超过后未上链的交易不会再上链
+    last_valid_block_height: u64,
+    timestamp: i64,
+}
+
+/// 已签名的交易，发送前写入进度文件
+#[derive(Clone, Copy, Debug)]
+struct SignedPayout {
+    signature: Signature,
+    last_valid_block_height: u64,
+}
+
+impl PayoutProgress {
+    fn signed(&self) -> Result<SignedPayout, Error> {
+        let signature = Signature::from_str(&self.signature).map_err(|_| {
+            Error::Internal(format!(
+                "invalid signature {} for line {} in the progress file",
+                self.signature, self.row
+            ))
+        })?;
+        Ok(SignedPayout {
+            signature,
+            last_valid_block_height: self.last_valid_block_height,
+        })
+    }
+}
+
+/// 回执文件中的一行
+#[derive(Serialize)]
+struct PayoutReceipt {
+    row: u64,
+    recipient: String,
+    amount: f64,
+    signature: String,
+}
+
+impl Miner {
+    // 按 CSV 文件批量转账：先校验全部地址，再创建缺少的代币账户并把尽量多的转账打包进一笔交易。
+    // 每笔交易签名后先把签名和对应的行记为 pending 再发送，确认后记为 sent；
+    // 中断后重新运行时先查询 pending 交易的链上结果，只重新发送确定没有上链的行，不会重复付款
+    pub async fn transfer_batch(&self, batch: &str, args: &TransferArgs) -> Result<(), Error> {
+        let payouts = parse_payouts(batch)?;
+        let progress_path = args
+            .progress_file
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| Path::new(batch).with_extension("progress.jsonl"));
+        let receipt_path = args
+            .receipt
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| Path::new(batch).with_extension("receipt.csv"));
+
+        // 与上次运行的进度核对，批量文件被修改过时拒绝继续
+        let mut progress = load_progress(&progress_path)?;
+        for record in progress.values() {
+            let matches = payouts.iter().any(|payout| {
+                payout.row.eq(&record.row)
+                    && payout.recipient.to_string().eq(&record.recipient)
+                    && payout.amount.eq(&record.amount)
+            });
+            if !matches {
+                return Err(Error::Internal(format!(
+                    "{} changed since the last run (line {}). Use a new --progress-file to start over.",
+                    batch, record.row
+                )));
+            }
+        }
+
+        // 查询 pending 交易：已上链的补记为 sent，区块哈希过期仍未上链或执行失败的重新发送
+        let mut signed_pending: Vec<(SignedPayout, Vec<Payout>)> = vec![];
+        for payout in payouts.iter() {
+            let Some(record) = progress.get(&payout.row) else {
+                continue;
+            };
+            if record.status.ne(&PayoutStatus::Pending) {
+                continue;
+            }
+            let signed = record.signed()?;
+            match signed_pending
+                .iter_mut()
+                .find(|(other, _)| other.signature.eq(&signed.signature))
+            {
+                Some((_, group)) => group.push(payout.clone()),
+                None => signed_pending.push((signed, vec![payout.clone()])),
+            }
+        }
+        for (signed, group) in signed_pending.iter() {
+            println!(
+                "Checking unconfirmed transaction {} (lines {})",
+                signed.signature,
+                rows(group)
+            );
+            match self.confirm_payout(*signed, None).await? {
+                Some(Ok(())) => {
+                    record_progress(&progress_path, group, PayoutStatus::Sent, signed)?;
+                    record_receipts(&receipt_path, group, &signed.signature.to_string())?;
+                    for payout in group.iter() {
+                        if let Some(record) = progress.get_mut(&payout.row) {
+                            record.status = PayoutStatus::Sent;
+                        }
+                    }
+                }
+                _ => {
+                    for payout in group.iter() {
+                        progress.remove(&payout.row);
+                    }
+                }
+            }
+        }
+
+        let todo: Vec<Payout> = payouts
+            .iter()
+            .filter(|payout| !progress.contains_key(&payout.row))
+            .cloned()
+            .collect();
+        if todo.is_empty() {
+            println!(
+                "Nothing to send: all {} transfers are recorded in {:?}",
+                payouts.len(),
+                progress_path
+            );
+            return Ok(());
+        }
+
+        // 查询接收者的代币账户，缺少的随转账一起创建
+        let signer = self.local_signer();
+        let mut recipients: Vec<Pubkey> = vec![];
+        for payout in todo.iter() {
+            if !recipients.contains(&payout.recipient) {
+                recipients.push(payout.recipient);
+            }
+        }
+        let token_accounts: Vec<Pubkey> = recipients
+            .iter()
+            .map(|recipient| get_associated_token_address(recipient, &MINT_ADDRESS))
+            .collect();
+        let mut missing = HashSet::new();
+        for (recipients, token_accounts) in recipients
+            .chunks(MAX_ACCOUNTS_PER_REQUEST)
+            .zip(token_accounts.chunks(MAX_ACCOUNTS_PER_REQUEST))
+        {
+            let accounts = self
+                .rpc_client
+                .get_multiple_accounts(token_accounts)
+                .await?;
+            for (recipient, account) in recipients.iter().zip(accounts.iter()) {
+                if account.is_none() {
+                    missing.insert(*recipient);
+                }
+            }
+        }
+
+        // 检查发送者的 ORE 余额是否足够
+        let total: u64 = todo.iter().map(|payout| payout.amount).sum();
+        let sender_tokens = get_associated_token_address(&signer.pubkey(), &MINT_ADDRESS);
+        let balance = self
+            .rpc_client
+            .get_token_account(&sender_tokens)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|account| account.token_amount.amount.parse::<u64>().ok())
+            .unwrap_or(0);
+        if balance.lt(&total) {
+            return Err(Error::Internal(format!(
+                "Insufficient ORE: the batch needs {} ORE but {} only holds {} ORE",
+                amount_u64_to_string(total),
+                signer.pubkey(),
+                amount_u64_to_string(balance)
+            )));
+        }
+
+        let txs = pack_payouts(&signer.pubkey(), &todo, &missing, args.max_per_tx.max(1));
+        if !ask_confirm(
+            format!(
+                "\nYou are about to transfer {} in {} transfers to {} recipients ({} new token accounts) in {} transactions.\n\nAre you sure you want to continue? [Y/n]",
+                format!("{} ORE", amount_to_ui_amount(total, TOKEN_DECIMALS)).bold(),
+                todo.len(),
+                recipients.len(),
+                missing.len(),
+                txs.len()
+            )
+            .as_str(),
+        ) {
+            return Ok(());
+        }
+
+        for (i, tx) in txs.iter().enumerate() {
+            println!(
+                "[{}/{}] Sending {} transfers (lines {})",
+                i + 1,
+                txs.len(),
+                tx.payouts.len(),
+                rows(&tx.payouts)
+            );
+            match self.send_payout_tx(tx, &progress_path).await {
+                Ok(signed) => {
+                    record_progress(&progress_path, &tx.payouts, PayoutStatus::Sent, &signed)?;
+                    record_receipts(&receipt_path, &tx.payouts, &signed.signature.to_string())?;
+                    println!("{} {}", "OK".bold().green(), signed.signature);
+                }
+                Err(err) => {
+                    return Err(Error::Internal(format!(
+                        "transaction for lines {} was not confirmed: {}. These transfers are marked pending in {:?}; rerun to check them on-chain and resend the ones that did not land.",
+                        rows(&tx.payouts),
+                        err,
+                        progress_path
+                    )));
+                }
+            }
+        }
+        println!(
+            "{} Sent {} ORE to {} recipients. Receipt: {:?}",
+            "OK".bold().green(),
+            amount_u64_to_string(total),
+            recipients.len(),
+            receipt_path
+        );
+        Ok(())
+    }
+
+    // 签名一次并把签名记入进度文件后才发送，在区块哈希过期前重复广播同一笔交易；
+    // 只有区块哈希过期且链上查不到旧签名时才用新的区块哈希重新签名
+    async fn send_payout_tx(
+        &self,
+        tx: &PayoutTx,
+        progress_path: &Path,
+    ) -> Result<SignedPayout, Error> {
+        for _ in 0..MAX_SIGNING_ATTEMPTS {
+            let fee_payer = self.select_fee_payer().await?;
+            let mut final_ixs = vec![
+                ComputeBudgetInstruction::set_compute_unit_limit(tx.compute_units),
+                ComputeBudgetInstruction::set_compute_unit_price(self.current_priority_fee().await),
+            ];
+            final_ixs.extend(tx.ixs.iter().cloned());
+            let mut transaction =
+                Transaction::new_with_payer(&final_ixs, Some(&fee_payer.pubkey()));
+            let (hash, last_valid_block_height) = self
+                .rpc_client
+                .get_latest_blockhash_with_commitment(self.rpc_client.commitment())
+                .await?;
+            self.sign_transaction(&mut transaction, hash, &fee_payer, &[])
+                .await?;
+            let signed = SignedPayout {
+                signature: transaction.signatures[0],
+                last_valid_block_height,
+            };
+            record_progress(progress_path, &tx.payouts, PayoutStatus::Pending, &signed)?;
+            match self.confirm_payout(signed, Some(&transaction)).await? {
+                Some(Ok(())) => return Ok(signed),
+                Some(Err(err)) => {
+                    return Err(Error::Internal(format!(
+                        "transaction {} failed: {}",
+                        signed.signature, err
+                    )));
+                }
+                None => println!(
+                    "{} Transaction {} expired without landing. Signing again...",
+                    "WARNING".bold().yellow(),
+                    signed.signature
+                ),
+            }
+        }
+        Err(Error::Internal(format!(
+            "no transaction landed after {} blockhashes",
+            MAX_SIGNING_ATTEMPTS
+        )))
+    }
+
+    // 等待已签名交易的结果，transaction 不为空时期间重复广播；
+    // 区块哈希过期后仍查不到签名时返回 None，此时交易不会再上链，可以安全地重新签名
+    async fn confirm_payout(
+        &self,
+        signed: SignedPayout,
+        transaction: Option<&Transaction>,
+    ) -> Result<Option<Result<(), TransactionError>>, Error> {
+        let send_cfg = RpcSendTransactionConfig {
+            skip_preflight: true,
+            ..RpcSendTransactionConfig::default()
+        };
+        loop {
+            if let Some(transaction) = transaction {
+                self.rpc_client
+                    .send_transaction_with_config(transaction, send_cfg)
+                    .await
+                    .ok();
+            }
+            if let Some(result) = self.payout_status(&signed.signature).await? {
+                return Ok(Some(result));
+            }
+            let block_height = self.rpc_client.get_block_height().await?;
+            if block_height.gt(&signed.last_valid_block_height) {
+                // 过期后再查询一次，避免遗漏在最后一个有效区块上链的交易
+                return self.payout_status(&signed.signature).await;
+            }
+            tokio::time::sleep(Duration::from_millis(CONFIRM_DELAY)).await;
+        }
+    }
+
+    // 查询签名的确认结果（包括历史记录），尚未确认时返回 None
+    async fn payout_status(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<Result<(), TransactionError>>, Error> {
+        let statuses = self
+            .rpc_client
+            .get_signature_statuses_with_history(&[*signature])
+            .await?;
+        Ok(statuses
+            .value
+            .into_iter()
+            .next()
+            .flatten()
+            .filter(|status| status.satisfies_commitment(CommitmentConfig::confirmed()))
+            .map(|status| status.status))
+    }
+}
+
+fn rows(payouts: &[Payout]) -> String {
+    payouts
+        .iter()
+        .map(|payout| payout.row.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// 读取并校验批量文件：每行为“地址,金额”，允许表头和以 # 开头的注释，有任何错误时一并列出
+fn parse_payouts(path: &str) -> Result<Vec<Payout>, Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .comment(Some(b'#'))
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .map_err(|err| Error::Internal(format!("failed to read {}: {}", path, err)))?;
+    let mut payouts = vec![];
+    let mut errors = vec![];
+    for (i, record) in reader.records().enumerate() {
+        let record = match record {
+            Ok(record) => record,
+            Err(err) => {
+                errors.push(err.to_string());
+                continue;
+            }
+        };
+        let row = record
+            .position()
+            .map_or(i as u64 + 1, |position| position.line());
+        let (Some(address), Some(amount), None) = (record.get(0), record.get(1), record.get(2))
+        else {
+            errors.push(format!("line {}: expected ADDRESS,AMOUNT", row));
+            continue;
+        };
+        let amount = amount.parse::<f64>();
+        if i.eq(&0) && amount.is_err() {
+            // 表头
+            continue;
+        }
+        let recipient = Pubkey::from_str(address);
+        // 小于最小单位的金额会变成 0，视为无效
+        let amount = amount
+            .ok()
+            .filter(|amount| amount.gt(&0.0) && amount.is_finite())
+            .map(amount_f64_to_u64)
+            .filter(|amount| amount.gt(&0));
+        match (recipient, amount) {
+            (Ok(recipient), Some(amount)) => {
+                payouts.push(Payout {
+                    row,
+                    recipient,
+                    amount,
+                });
+            }
+            (Err(_), _) => errors.push(format!("line {}: invalid address {}", row, address)),
+            _ => errors.push(format!(
+                "line {}: invalid amount {}",
+                row,
+                record.get(1).unwrap_or_default()
+            )),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(Error::Internal(format!(
+            "{} has {} invalid lines:\n{}",
+            path,
+            errors.len(),
+            errors.join("\n")
+        )));
+    }
+    if payouts.is_empty() {
+        return Err(Error::Internal(format!("{} has no transfers", path)));
+    }
+    Ok(payouts)
+}
+
+// 把转账按交易大小和数量上限依次打包，缺少的代币账户只在第一次转账前创建
+fn pack_payouts(
+    sender: &Pubkey,
+    payouts: &[Payout],
+    missing: &HashSet<Pubkey>,
+    max_per_tx: usize,
+) -> Vec<PayoutTx> {
+    let mut txs: Vec<PayoutTx> = vec![];
+    let mut created: HashSet<Pubkey> = HashSet::new();
+    let mut current = PayoutTx {
+        payouts: vec![],
+        ixs: vec![],
+        compute_units: CU_MARGIN,
+    };
+    let mut current_created: HashSet<Pubkey> = HashSet::new();
+    for payout in payouts.iter() {
+        let (ixs, compute_units, creates) =
+            payout_instructions(sender, payout, missing, &created, &current_created);
+        let mut candidate = current.ixs.clone();
+        candidate.extend(ixs.iter().cloned());
+        let fits = current.payouts.len().lt(&max_per_tx)
+            && transaction_size(&candidate).le(&PACKET_DATA_SIZE);
+        if !fits && !current.payouts.is_empty() {
+            created.extend(current_created.drain());
+            txs.push(std::mem::replace(
+                &mut current,
+                PayoutTx {
+                    payouts: vec![],
+                    ixs: vec![],
+                    compute_units: CU_MARGIN,
+                },
+            ));
+            let (ixs, compute_units, creates) =
+                payout_instructions(sender, payout, missing, &created, &current_created);
+            current.ixs.extend(ixs);
+            current.compute_units += compute_units;
+            current_created.extend(creates);
+        } else {
+            current.ixs = candidate;
+            current.compute_units += compute_units;
+            current_created.extend(creates);
+        }
+        current.payouts.push(payout.clone());
+    }
+    if !current.payouts.is_empty() {
+        txs.push(current);
+    }
+    txs
+}
+
+// 一笔转账的指令：必要时先创建接收者的代币账户
+fn payout_instructions(
+    sender: &Pubkey,
+    payout: &Payout,
+    missing: &HashSet<Pubkey>,
+    created: &HashSet<Pubkey>,
+    current_created: &HashSet<Pubkey>,
+) -> (Vec<Instruction>, u32, Option<Pubkey>) {
+    let mut ixs = vec![];
+    let mut compute_units = CU_TRANSFER;
+    let mut creates = None;
+    if missing.contains(&payout.recipient)
+        && !created.contains(&payout.recipient)
+        && !current_created.contains(&payout.recipient)
+    {
+        ixs.push(create_associated_token_account_idempotent(
+            sender,
+            &payout.recipient,
+            &MINT_ADDRESS,
+            &spl_token::id(),
+        ));
+        compute_units += CU_CREATE_ATA;
+        creates = Some(payout.recipient);
+    }
+    let sender_tokens = get_associated_token_address(sender, &MINT_ADDRESS);
+    let recipient_tokens = get_associated_token_address(&payout.recipient, &MINT_ADDRESS);
+    ixs.push(
+        spl_token::instruction::transfer(
+            &spl_token::id(),
+            &sender_tokens,
+            &recipient_tokens,
+            sender,
+            &[sender],
+            payout.amount,
+        )
+        .expect("Failed to create transfer instruction"),
+    );
+    (ixs, compute_units, creates)
+}
+
+// 估算交易序列化后的大小，包括 send_and_confirm 添加的计算预算指令和单独的费用支付者签名
+fn transaction_size(ixs: &[Instruction]) -> usize {
+    let mut final_ixs = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(0),
+        ComputeBudgetInstruction::set_compute_unit_price(0),
+    ];
+    final_ixs.extend_from_slice(ixs);
+    let fee_payer = Pubkey::new_unique();
+    let tx = Transaction::new_with_payer(&final_ixs, Some(&fee_payer));
+    bincode::serialized_size(&tx).map_or(usize::MAX, |size| size as usize)
+}
+
+fn load_progress(path: &Path) -> Result<HashMap<u64, PayoutProgress>, Error> {
+    let Ok(data) = fs::read_to_string(path) else {
+        return Ok(HashMap::new());
+    };
+    let mut progress = HashMap::new();
+    for line in data.lines().filter(|line| !line.trim().is_empty()) {
+        let record: PayoutProgress = serde_json::from_str(line)
+            .map_err(|err| Error::Internal(format!("failed to parse {:?}: {}", path, err)))?;
+        progress.insert(record.row, record);
+    }
+    Ok(progress)
+}
+
+// 追加进度记录并立即落盘，发送交易前必须记录成功
+fn record_progress(
+    path: &Path,
+    payouts: &[Payout],
+    status: PayoutStatus,
+    signed: &SignedPayout,
+) -> Result<(), Error> {
+    let result = (|| {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        for payout in payouts.iter() {
+            let record = PayoutProgress {
+                row: payout.row,
+                recipient: payout.recipient.to_string(),
+                amount: payout.amount,
+                status,
+                signature: signed.signature.to_string(),
+                last_valid_block_height: signed.last_valid_block_height,
+                timestamp: Utc::now().timestamp(),
+            };
+            writeln!(file, "{}", serde_json::to_string(&record)?)?;
+        }
+        file.sync_all()
+    })();
+    result.map_err(|err| Error::Internal(format!("failed to write {:?}: {}", path, err)))
+}
+
+fn record_receipts(path: &Path, payouts: &[Payout], signature: &str) -> Result<(), Error> {
+    let exists = path.exists();
+    let result = (|| {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(!exists)
+            .from_writer(file);
+        for payout in payouts.iter() {
+            writer.serialize(PayoutReceipt {
+                row: payout.row,
+                recipient: payout.recipient.to_string(),
+                amount: amount_to_ui_amount(payout.amount, TOKEN_DECIMALS),
+                signature: signature.to_string(),
+            })?;
+        }
+        writer.flush()?;
+        Ok::<(), csv::Error>(())
+    })();
+    result.map_err(|err| Error::Internal(format!("failed to write {:?}: {}", path, err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_batch(contents: &str) -> String {
+        let path = std::env::temp_dir().join(format!("batch-{}.csv", rand::random::<u64>()));
+        fs::write(&path, contents).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    fn payout(row: u64, recipient: Pubkey) -> Payout {
+        Payout {
+            row,
+            recipient,
+            amount: 1_000,
+        }
+    }
+
+    fn count_creates(tx: &PayoutTx) -> usize {
+        tx.ixs
+            .iter()
+            .filter(|ix| ix.program_id.eq(&spl_associated_token_account::id()))
+            .count()
+    }
+
+    #[test]
+    fn parse_payouts_skips_header_and_comments() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let path = write_batch(&format!(
+            "address,amount\n# first payout\n{},1.5\n{}, 2\n",
+            a, b
+        ));
+        let payouts = parse_payouts(&path).unwrap();
+        assert_eq!(
+            payouts,
+            vec![
+                Payout {
+                    row: 3,
+                    recipient: a,
+                    amount: amount_f64_to_u64(1.5),
+                },
+                Payout {
+                    row: 4,
+                    recipient: b,
+                    amount: amount_f64_to_u64(2.0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_payouts_reports_every_bad_row() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let path = write_batch(&format!(
+            "{a},1\nnot-an-address,1\n{b},abc\n{a},0\n{b},0.000000000000001\n{a},-1\n"
+        ));
+        let err = parse_payouts(&path).unwrap_err().to_string();
+        assert!(err.contains("has 5 invalid lines"), "{}", err);
+        assert!(err.contains("line 2: invalid address not-an-address"));
+        assert!(err.contains("line 3: invalid amount abc"));
+        assert!(err.contains("line 4: invalid amount 0"));
+        // 小于最小单位的金额会变成 0
+        assert!(err.contains("line 5: invalid amount 0.000000000000001"));
+        assert!(err.contains("line 6: invalid amount -1"));
+    }
+
+    #[test]
+    fn parse_payouts_rejects_empty_batch() {
+        let path = write_batch("address,amount\n# nothing yet\n");
+        let err = parse_payouts(&path).unwrap_err().to_string();
+        assert!(err.contains("has no transfers"), "{}", err);
+    }
+
+    #[test]
+    fn pack_payouts_fits_packet_size() {
+        let sender = Pubkey::new_unique();
+        let payouts: Vec<Payout> = (0..40)
+            .map(|row| payout(row, Pubkey::new_unique()))
+            .collect();
+        let missing: HashSet<Pubkey> = payouts.iter().map(|payout| payout.recipient).collect();
+        let txs = pack_payouts(&sender, &payouts, &missing, 100);
+        assert!(txs.len().gt(&1));
+        for tx in txs.iter() {
+            assert!(transaction_size(&tx.ixs).le(&PACKET_DATA_SIZE));
+        }
+        let packed: Vec<Payout> = txs.iter().flat_map(|tx| tx.payouts.clone()).collect();
+        assert_eq!(packed, payouts);
+    }
+
+    #[test]
+    fn pack_payouts_respects_max_per_tx() {
+        let sender = Pubkey::new_unique();
+        let payouts: Vec<Payout> = (0..5)
+            .map(|row| payout(row, Pubkey::new_unique()))
+            .collect();
+        let txs = pack_payouts(&sender, &payouts, &HashSet::new(), 2);
+        let sizes: Vec<usize> = txs.iter().map(|tx| tx.payouts.len()).collect();
+        assert_eq!(sizes, vec![2, 2, 1]);
+        assert!(txs.iter().all(|tx| tx.ixs.len().eq(&tx.payouts.len())));
+    }
+
+    #[test]
+    fn pack_payouts_creates_token_account_once() {
+        let sender = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let payouts: Vec<Payout> = (0..3).map(|row| payout(row, recipient)).collect();
+        let missing = HashSet::from([recipient]);
+
+        // 同一笔交易中只创建一次
+        let txs = pack_payouts(&sender, &payouts, &missing, 10);
+        assert_eq!(txs.len(), 1);
+        assert_eq!(count_creates(&txs[0]), 1);
+
+        // 分到多笔交易时只在第一笔中创建
+        let txs = pack_payouts(&sender, &payouts, &missing, 1);
+        let creates: Vec<usize> = txs.iter().map(count_creates).collect();
+        assert_eq!(creates, vec![1, 0, 0]);
+        assert_eq!(
+            txs[0].compute_units,
+            CU_MARGIN + CU_TRANSFER + CU_CREATE_ATA
+        );
+        assert_eq!(txs[1].compute_units, CU_MARGIN + CU_TRANSFER);
+    }
+}
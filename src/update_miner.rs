@@ -0,0 +1,43 @@
+use std::str::FromStr;
+
+use colored::*;
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    args::UpdateMinerArgs,
+    error::Error,
+    remote_signer::connect_remote_signer,
+    send_and_confirm::ComputeBudget,
+    utils::{ask_confirm, get_proof_with_authority},
+    Miner,
+};
+
+impl Miner {
+    // 更换证明的矿工密钥，只需要 authority 签名；旧的矿工密钥随即失去挖矿权限
+    pub async fn update_miner(&self, args: UpdateMinerArgs) -> Result<(), Error> {
+        self.select_signer(&args.signer)?;
+        if let Some(ref url) = args.remote.remote_signer {
+            connect_remote_signer(url).await?;
+        }
+        let authority = self.authority();
+        let miner = Pubkey::from_str(&args.miner)
+            .map_err(|_| Error::Internal("invalid miner address".to_string()))?;
+        let proof = get_proof_with_authority(&self.rpc_client, authority).await;
+        println!("{}: {}", "Authority".bold(), authority);
+        println!("{}: {}", "Current miner".bold(), proof.miner);
+        println!("{}: {}", "New miner".bold(), miner);
+        if proof.miner.eq(&miner) {
+            println!("Proof already uses this miner key.");
+            return Ok(());
+        }
+        if !ask_confirm(
+            "\nThe current miner key will no longer be able to mine with this proof.\n\nAre you sure you want to continue? [Y/n]",
+        ) {
+            return Ok(());
+        }
+        let ix = ore_api::instruction::update(authority, miner);
+        self.send_and_confirm(&[ix], ComputeBudget::Fixed(50_000), false)
+            .await?;
+        Ok(())
+    }
+}
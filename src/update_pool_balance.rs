@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use colored::*;
+
+use crate::{
+    args::UpdatePoolBalanceArgs,
+    error::Error,
+    pool::{Member, Pool},
+    utils::amount_u64_to_string,
+    Miner,
+};
+
+const UPDATE_BALANCE_RETRIES: usize = 30; // 等待链上余额更新的最大次数
+const UPDATE_BALANCE_DELAY: u64 = 2_000; // 每次检查链上余额的间隔（毫秒）
+
+impl Miner {
+    // 将矿池中的链下余额提交上链，使可领取余额与矿池记录保持一致
+    pub async fn update_pool_balance(&self, args: UpdatePoolBalanceArgs) -> Result<(), Error> {
+        self.select_signer(&args.signer)?;
+        let pool = Pool::new(args.pool_url);
+        let pool_address = pool.get_pool_address().await?;
+        pool.load_pool_operator(self, pool_address.address, args.pool_operator.as_slice())
+            .await?;
+
+        // 比较链下记录和链上账户；只在链下记账的矿池没有链上成员账户，改为请求运营商直接支付
+        let signer = self.local_signer();
+        let member = pool.get_pool_member(&signer).await?;
+        let Ok(before) = pool
+            .get_pool_member_onchain(self, pool_address.address)
+            .await
+        else {
+            return self.pay_out_pool_balance(&pool, &member).await;
+        };
+        let offchain_total = member.total_balance.max(0) as u64;
+        let pending = offchain_total.saturating_sub(before.total_balance);
+        println!(
+            "{}: {} ORE",
+            "Off-chain total".bold(),
+            amount_u64_to_string(offchain_total)
+        );
+        println!(
+            "{}: {} ORE",
+            "On-chain total".bold(),
+            amount_u64_to_string(before.total_balance)
+        );
+        println!(
+            "{}: {} ORE",
+            "Claimable".bold(),
+            amount_u64_to_string(before.balance)
+        );
+        println!(
+            "{}: {} ORE",
+            "Difference".bold(),
+            amount_u64_to_string(pending)
+        );
+        if pending.eq(&0) {
+            println!("On-chain balance is up to date.");
+            return Ok(());
+        }
+
+        // 请求矿池运营商提交余额
+        let commit = pool.post_update_pool_balance(&signer).await?;
+        println!(
+            "Pool committed {} ORE: {}",
+            amount_u64_to_string(commit.total_balance),
+            commit.signature
+        );
+
+        // 等待链上余额更新
+        for _ in 0..UPDATE_BALANCE_RETRIES {
+            let after = pool
+                .get_pool_member_onchain(self, pool_address.address)
+                .await?;
+            if after.total_balance.ge(&commit.total_balance) {
+                println!(
+                    "{}: {} ORE (+{} ORE)",
+                    "Claimable".bold(),
+                    amount_u64_to_string(after.balance),
+                    amount_u64_to_string(after.balance.saturating_sub(before.balance))
+                );
+                println!(
+                    "{}: {} ORE",
+                    "Difference".bold(),
+                    amount_u64_to_string(offchain_total.saturating_sub(after.total_balance))
+                );
+                return Ok(());
+            }
+            tokio::time::sleep(Duration::from_millis(UPDATE_BALANCE_DELAY)).await;
+        }
+        Err(Error::Internal(
+            "timed out waiting for the on-chain balance to update".to_string(),
+        ))
+    }
+
+    // 只在链下记账的矿池没有链上成员账户，运营商直接把尚未支付的余额转入成员的代币账户
+    async fn pay_out_pool_balance(&self, pool: &Pool, member: &Member) -> Result<(), Error> {
+        println!(
+            "{}: {} ORE",
+            "Off-chain total".bold(),
+            amount_u64_to_string(member.total_balance.max(0) as u64)
+        );
+        let commit = pool.post_update_pool_balance(&self.local_signer()).await?;
+        if commit.paid.eq(&0) {
+            println!(
+                "Nothing to pay out. {} ORE has already been paid.",
+                amount_u64_to_string(commit.total_balance)
+            );
+            return Ok(());
+        }
+        println!(
+            "Pool paid out {} ORE to your wallet: {}",
+            amount_u64_to_string(commit.paid),
+            commit.signature
+        );
+        println!(
+            "{}: {} ORE",
+            "Total paid".bold(),
+            amount_u64_to_string(commit.total_balance)
+        );
+        Ok(())
+    }
+}
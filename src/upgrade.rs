@@ -12,8 +12,12 @@ use crate::{
 impl Miner {
     // 用于升级矿工的代币账户
     pub async fn upgrade(&self, args: UpgradeArgs) {
+        if let Err(err) = self.select_signer(&args.signer) {
+            eprintln!("{} {}", "ERROR".bold().red(), err);
+            return;
+        }
         // 获取签名者(矿工的公钥)
-        let signer = &self.signer();
+        let signer = &self.local_signer();
         // 获取或初始化关联代币账户（ATA）
         let beneficiary = self.get_or_initialize_ata().await;
         // 获取 v1 代币账户地址和余额
@@ -62,7 +66,7 @@ impl Miner {
     // 确保代币账户存在并获取余额
     async fn get_ata_v1(&self) -> (Pubkey, f64) {
         // 获取签名者
-        let signer = self.signer();
+        let signer = self.local_signer();
         // 克隆RPC客户端
         let client = self.rpc_client.clone();
 
@@ -101,7 +105,7 @@ impl Miner {
     // 确保关联代币账户存在，如果不存在则初始化
     async fn get_or_initialize_ata(&self) -> Pubkey {
         // 获取签名者
-        let signer = self.signer();
+        let signer = self.local_signer();
         // 克隆RPC客户端
         let client = self.rpc_client.clone();
 
@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{path::PathBuf, time::Duration};
 
 use cached::proc_macro::cached; // 引入缓存宏
 use luckycoin_api::consts::PROOF; // 引入常量 PROOF
@@ -12,6 +12,15 @@ use tokio::time::sleep; // 引入异步睡眠功能
 pub const BLOCKHASH_QUERY_RETRIES: usize = 5; // 查询最新区块哈希的最大重试次数
 pub const BLOCKHASH_QUERY_DELAY: u64 = 500; // 查询延迟，单位为毫秒
 
+// getMultipleAccounts 单次最多查询的账户数
+pub const MAX_ACCOUNTS_PER_REQUEST: usize = 100;
+
+/// CLI 本地数据文件的路径（~/.config/luckycoin/<file>）
+pub fn config_path(file: &str) -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or(".".to_string()); // 无法获取主目录时使用当前目录
+    PathBuf::from(home).join(".config").join("luckycoin").join(file)
+}
+
 /// 计算并缓存给定 authority 的证明公钥
 #[cached]
 pub fn proof_pubkey(authority: Pubkey) -> Pubkey {
@@ -0,0 +1,142 @@
+use colored::*;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+
+use crate::{
+    args::{WalletArgs, WalletCommand, WalletImportArgs, WalletNameArgs, WalletNewArgs},
+    error::Error,
+    keystore::{
+        list_wallets, load_wallet, prompt_new_passphrase, remove_wallet, save_wallet,
+        validate_name, wallet_exists, EncryptedWallet,
+    },
+    mnemonic::{
+        account_derivation_path, generate_mnemonic, keypair_from_mnemonic, parse_derivation_path,
+        prompt_mnemonic,
+    },
+    utils::ask_confirm,
+    Miner,
+};
+
+impl Miner {
+    pub async fn wallet(&self, args: WalletArgs) -> Result<(), Error> {
+        match args.command {
+            WalletCommand::New(args) => self.wallet_new(args),
+            WalletCommand::Import(args) => self.wallet_import(args),
+            WalletCommand::List(_) => self.wallet_list(),
+            WalletCommand::ExportPubkey(args) => self.wallet_export_pubkey(args),
+            WalletCommand::Remove(args) => self.wallet_remove(args),
+        }
+    }
+
+    // 生成新的密钥对并加密保存，可选从新生成的助记词派生
+    fn wallet_new(&self, args: WalletNewArgs) -> Result<(), Error> {
+        if !args.mnemonic {
+            return self.store_wallet(&args.name, &Keypair::new());
+        }
+        let derivation_path = parse_derivation_path(args.derivation_path.as_deref())?;
+        let mnemonic = generate_mnemonic(args.words)?;
+        let keypair = keypair_from_mnemonic(&mnemonic, "", &derivation_path)?;
+        self.store_wallet(&args.name, &keypair)?;
+        println!(
+            "\n{}\n{}\n\nWrite this seed phrase down and keep it safe. It is the only way to recover the wallet if the keystore is lost.",
+            "Seed phrase:".bold(),
+            mnemonic.phrase()
+        );
+        Ok(())
+    }
+
+    // 将明文密钥对文件或助记词加密导入钱包
+    fn wallet_import(&self, args: WalletImportArgs) -> Result<(), Error> {
+        if let Some(ref path) = args.keypair {
+            let keypair = read_keypair_file(path)
+                .map_err(|err| Error::Keystore(format!("failed to read {}: {}", path, err)))?;
+            self.store_wallet(&args.name, &keypair)?;
+            println!(
+                "The plaintext keypair at {} is no longer needed and can be deleted.",
+                path
+            );
+            return Ok(());
+        }
+
+        // 从助记词恢复，--count 时按账户序号批量派生
+        let (mnemonic, passphrase) = prompt_mnemonic()?;
+        match args.count {
+            Some(count) => {
+                let names: Vec<String> = (0..count)
+                    .map(|index| format!("{}-{}", args.name, index))
+                    .collect();
+                for name in names.iter() {
+                    validate_name(name)?;
+                    if wallet_exists(name) {
+                        return Err(Error::Keystore(format!("wallet {} already exists", name)));
+                    }
+                }
+                let wallet_passphrase = prompt_new_passphrase()?;
+                for (index, name) in names.iter().enumerate() {
+                    let keypair = keypair_from_mnemonic(
+                        &mnemonic,
+                        &passphrase,
+                        &account_derivation_path(index as u32),
+                    )?;
+                    save_wallet(&EncryptedWallet::encrypt(
+                        name,
+                        &keypair,
+                        &wallet_passphrase,
+                    )?)?;
+                    println!("{:<20} {}", name, keypair.pubkey());
+                }
+                Ok(())
+            }
+            None => {
+                let derivation_path = parse_derivation_path(args.derivation_path.as_deref())?;
+                let keypair = keypair_from_mnemonic(&mnemonic, &passphrase, &derivation_path)?;
+                self.store_wallet(&args.name, &keypair)
+            }
+        }
+    }
+
+    fn wallet_list(&self) -> Result<(), Error> {
+        let wallets = list_wallets();
+        if wallets.is_empty() {
+            println!("No wallets found. Create one with `wallet new <NAME>`.");
+            return Ok(());
+        }
+        for wallet in wallets {
+            println!("{:<20} {}", wallet.name, wallet.pubkey);
+        }
+        Ok(())
+    }
+
+    fn wallet_export_pubkey(&self, args: WalletNameArgs) -> Result<(), Error> {
+        println!("{}", load_wallet(&args.name)?.pubkey);
+        Ok(())
+    }
+
+    fn wallet_remove(&self, args: WalletNameArgs) -> Result<(), Error> {
+        let wallet = load_wallet(&args.name)?;
+        if !ask_confirm(
+            format!(
+                "\nYou are about to delete wallet {} ({}). Without a backup its funds cannot be recovered.\n\nAre you sure you want to continue? [Y/n]",
+                wallet.name.bold(),
+                wallet.pubkey
+            )
+            .as_str(),
+        ) {
+            return Ok(());
+        }
+        remove_wallet(&args.name)?;
+        println!("Removed wallet {}", args.name);
+        Ok(())
+    }
+
+    fn store_wallet(&self, name: &str, keypair: &Keypair) -> Result<(), Error> {
+        validate_name(name)?;
+        if wallet_exists(name) {
+            return Err(Error::Keystore(format!("wallet {} already exists", name)));
+        }
+        let passphrase = prompt_new_passphrase()?;
+        save_wallet(&EncryptedWallet::encrypt(name, keypair, &passphrase)?)?;
+        println!("{}: {}", "Wallet".bold(), name);
+        println!("{}: {}", "Pubkey".bold(), keypair.pubkey());
+        Ok(())
+    }
+}